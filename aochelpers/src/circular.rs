@@ -0,0 +1,131 @@
+/// A circular sequence which supports moving elements by an offset, as when mixing in 2022
+/// day 20, in O(√n) time per move.
+///
+/// Each element keeps the id it was given when the list was built (its original index), so it
+/// can be found and moved however far it has travelled. Positions are counted from an
+/// arbitrary but consistent start of the circle; `nth_after()` counts round from a given element.
+///
+/// Internally the elements are split into blocks of around √n ids, and each element remembers
+/// which block it is in, so elements are located without searching the whole list.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::CircularList;
+/// let mut list = [1, 2, -3, 3, -2, 0, 4].into_iter().collect::<CircularList<i64>>();
+/// for id in 0..list.len() {
+///     list.move_by(id, list[id]);
+/// }
+/// let zero = list.find(|v| *v == 0).unwrap();
+/// assert_eq!(list.nth_after(zero, 1000) + list.nth_after(zero, 2000) + list.nth_after(zero, 3000), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircularList<T> {
+    values: Vec<T>,
+    blocks: Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+    block_size: usize
+}
+
+impl<T> FromIterator<T> for CircularList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values = iter.into_iter().collect::<Vec<_>>();
+        let mut list = CircularList { blocks: Vec::new(), block_of: vec![0; values.len()], block_size: 1, values };
+        list.rebuild((0..list.values.len()).collect());
+        list
+    }
+}
+
+impl<T> std::ops::Index<usize> for CircularList<T> {
+    type Output = T;
+    /// The value of the element with the given id
+    fn index(&self, id: usize) -> &T {
+        &self.values[id]
+    }
+}
+
+impl<T> CircularList<T> {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Re-splits the ids, in the given order, into evenly sized blocks
+    fn rebuild(&mut self, order: Vec<usize>) {
+        self.block_size = ((order.len() as f64).sqrt() as usize).max(1);
+        self.blocks = order.chunks(self.block_size).map(|c| c.to_vec()).collect();
+        for (b, block) in self.blocks.iter().enumerate() {
+            for id in block {
+                self.block_of[*id] = b;
+            }
+        }
+    }
+
+    /// The ids of the elements, in order round the circle
+    pub fn ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().flatten().copied()
+    }
+
+    /// The values of the elements, in order round the circle
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.ids().map(|id| &self.values[id])
+    }
+
+    /// The id of the first element (counting from the start of the circle) whose value matches
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<usize> {
+        self.ids().find(|id| predicate(&self.values[*id]))
+    }
+
+    /// Where the element with the given id currently is, counting from the start of the circle
+    pub fn position(&self, id: usize) -> usize {
+        let block = self.block_of[id];
+        let before = self.blocks[..block].iter().map(|b| b.len()).sum::<usize>();
+        before + self.blocks[block].iter().position(|i| *i == id).unwrap()
+    }
+
+    /// The id of the element at `position`, which wraps round the circle
+    pub fn id_at(&self, position: usize) -> usize {
+        let mut remaining = position % self.len();
+        for block in self.blocks.iter() {
+            if remaining < block.len() {
+                return block[remaining];
+            }
+            remaining -= block.len();
+        }
+        unreachable!()
+    }
+
+    /// The value `offset` places after the element with the given id. Negative offsets count backwards.
+    pub fn nth_after(&self, id: usize, offset: i64) -> &T {
+        let position = (self.position(id) as i64 + offset).rem_euclid(self.len() as i64) as usize;
+        &self.values[self.id_at(position)]
+    }
+
+    /// Takes the element with the given id out of the circle, and puts it back `offset` places
+    /// further on, counted among the other elements, so moving by `len() - 1` has no effect.
+    pub fn move_by(&mut self, id: usize, offset: i64) {
+        if self.len() < 2 {
+            return;
+        }
+        let position = self.position(id);
+        let block = self.block_of[id];
+        self.blocks[block].retain(|i| *i != id);
+        let mut remaining = (position as i64 + offset).rem_euclid(self.len() as i64 - 1) as usize;
+        let mut target = self.blocks.len() - 1;
+        for (b, block) in self.blocks.iter().enumerate() {
+            if remaining <= block.len() {
+                target = b;
+                break;
+            }
+            remaining -= block.len();
+        }
+        self.blocks[target].insert(remaining, id);
+        self.block_of[id] = target;
+        if self.blocks[target].len() > 2 * self.block_size {
+            let order = self.ids().collect();
+            self.rebuild(order);
+        }
+    }
+}
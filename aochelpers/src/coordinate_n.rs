@@ -0,0 +1,154 @@
+use std::{fmt::{self, Display}, ops::{Add, Sub, AddAssign, SubAssign, Index, IndexMut}};
+use num::Integer;
+use crate::{Coordinate, Coordinate3d};
+
+/// A Cartesian Coordinate with an arbitrary number of dimensions `D`.
+///
+/// Useful for cellular automata puzzles which move into 4 or more dimensions.
+/// Individual axes can be read or written by index: `coordinate[0]` is the X axis,
+/// `coordinate[1]` the Y axis and so on.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct CoordinateN<T, const D: usize> {
+    pub values: [T; D]
+}
+
+impl<T, const D: usize> CoordinateN<T, D> {
+    pub fn new(values: [T; D]) -> Self {
+        CoordinateN { values }
+    }
+}
+
+impl<T: Integer + Copy, const D: usize> CoordinateN<T, D> {
+    /// The coordinate at which every axis is zero
+    pub fn origin() -> Self {
+        CoordinateN { values: [num::zero(); D] }
+    }
+
+    /// Taxicab / manhattan distance: the sum of the differences along each axis
+    pub fn manhattan_distance(&self, other: &Self) -> T {
+        self.values.iter().zip(other.values.iter())
+            .fold(num::zero(), |acc, (a, b)| acc + *a.max(b) - *a.min(b))
+    }
+
+    /// Chebyshev / chessboard distance: the largest difference along any single axis
+    pub fn chebyshev_distance(&self, other: &Self) -> T {
+        self.values.iter().zip(other.values.iter())
+            .fold(num::zero(), |acc, (a, b)| acc.max(*a.max(b) - *a.min(b)))
+    }
+
+    /// All coordinates directly neighbouring this one along a single axis (`2 * D` in total)
+    pub fn neighbours(&self) -> impl Iterator<Item = Self> {
+        let origin = *self;
+        (0..D).flat_map(move |axis| {
+            let mut lower = origin;
+            let mut upper = origin;
+            lower.values[axis] = lower.values[axis] - num::one();
+            upper.values[axis] = upper.values[axis] + num::one();
+            [lower, upper]
+        })
+    }
+
+    /// All coordinates directly neighbouring this one, including diagonals (`3^D - 1` in total).
+    ///
+    /// This is the Moore neighbourhood used by Conway-style cellular automata.
+    pub fn extended_neighbours(&self) -> impl Iterator<Item = Self> {
+        let origin = *self;
+        let count = 3_usize.pow(D as u32);
+        let centre = count / 2;
+        (0..count).filter(move |i| *i != centre).map(move |mut i| {
+            let mut neighbour = origin;
+            for value in neighbour.values.iter_mut() {
+                match i % 3 {
+                    0 => *value = *value - num::one(),
+                    2 => *value = *value + num::one(),
+                    _ => {}
+                }
+                i /= 3;
+            }
+            neighbour
+        })
+    }
+}
+
+/// Renders CoordinateN as `(a,b,c,...)`
+impl<T: Display, const D: usize> Display for CoordinateN<T, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values = self.values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        write!(f, "({})", values.join(","))
+    }
+}
+
+impl<T, const D: usize> Index<usize> for CoordinateN<T, D> {
+    type Output = T;
+    fn index(&self, axis: usize) -> &T {
+        &self.values[axis]
+    }
+}
+
+impl<T, const D: usize> IndexMut<usize> for CoordinateN<T, D> {
+    fn index_mut(&mut self, axis: usize) -> &mut T {
+        &mut self.values[axis]
+    }
+}
+
+impl<T: Add<Output = T> + Copy, const D: usize> Add for CoordinateN<T, D> {
+    type Output = Self;
+    fn add(mut self, other: Self) -> Self {
+        for (value, delta) in self.values.iter_mut().zip(other.values) {
+            *value = *value + delta;
+        }
+        self
+    }
+}
+
+impl<T: Sub<Output = T> + Copy, const D: usize> Sub for CoordinateN<T, D> {
+    type Output = Self;
+    fn sub(mut self, other: Self) -> Self {
+        for (value, delta) in self.values.iter_mut().zip(other.values) {
+            *value = *value - delta;
+        }
+        self
+    }
+}
+
+impl<T: AddAssign, const D: usize> AddAssign for CoordinateN<T, D> {
+    fn add_assign(&mut self, other: Self) {
+        for (value, delta) in self.values.iter_mut().zip(other.values) {
+            *value += delta;
+        }
+    }
+}
+
+impl<T: SubAssign, const D: usize> SubAssign for CoordinateN<T, D> {
+    fn sub_assign(&mut self, other: Self) {
+        for (value, delta) in self.values.iter_mut().zip(other.values) {
+            *value -= delta;
+        }
+    }
+}
+
+impl<T> From<Coordinate<T>> for CoordinateN<T, 2> {
+    fn from(other: Coordinate<T>) -> Self {
+        CoordinateN { values: [other.x, other.y] }
+    }
+}
+
+impl<T> From<Coordinate3d<T>> for CoordinateN<T, 3> {
+    fn from(other: Coordinate3d<T>) -> Self {
+        CoordinateN { values: [other.x, other.y, other.z] }
+    }
+}
+
+impl<T> From<CoordinateN<T, 2>> for Coordinate<T> {
+    fn from(other: CoordinateN<T, 2>) -> Self {
+        let [x, y] = other.values;
+        Coordinate { x, y }
+    }
+}
+
+impl<T> From<CoordinateN<T, 3>> for Coordinate3d<T> {
+    fn from(other: CoordinateN<T, 3>) -> Self {
+        let [x, y, z] = other.values;
+        Coordinate3d { x, y, z }
+    }
+}
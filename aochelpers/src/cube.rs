@@ -0,0 +1,192 @@
+use std::{fmt::{self, Display}, error::Error, collections::{HashMap, HashSet, VecDeque}};
+use crate::{Coordinate, Direction};
+
+type Vector = [i32; 3];
+
+fn negate(v: Vector) -> Vector {
+    [-v[0], -v[1], -v[2]]
+}
+
+fn dot(a: Vector, b: Vector) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Returned when a set of tiles can't be folded into a cube
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CubeNetError {
+    /// The number of tiles isn't six times a square number
+    TileCount(usize),
+    /// The face whose top left corner is at `corner` is only partly filled, or the tiles don't
+    /// divide into exactly six faces
+    Faces { corner: Option<Coordinate<i32>> },
+    /// The faces are connected, but folding them up makes two faces overlap
+    Overlap
+}
+
+impl Display for CubeNetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CubeNetError::TileCount(count) => write!(f, "{} tiles can't make six square faces", count),
+            CubeNetError::Faces { corner: Some(corner) } => write!(f, "Face at {} is incomplete", corner),
+            CubeNetError::Faces { corner: None } => write!(f, "Tiles don't form six connected faces"),
+            CubeNetError::Overlap => write!(f, "Faces overlap when folded")
+        }
+    }
+}
+
+impl Error for CubeNetError {}
+
+/// Where a face of the cube sits, both in the net and in 3D.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Face {
+    /// Top left tile of the face in the net
+    corner: Coordinate<i32>,
+    /// Directions of increasing x and y within the face, and the outward normal, once folded
+    right: Vector,
+    down: Vector,
+    normal: Vector
+}
+
+impl Face {
+    /// The face reached by folding over the edge in `direction`
+    fn fold(&self, direction: Direction) -> (Vector, Vector, Vector) {
+        let Face { right, down, normal, .. } = *self;
+        match direction {
+            Direction::East => (negate(normal), down, right),
+            Direction::West => (normal, down, negate(right)),
+            Direction::South => (right, negate(normal), down),
+            Direction::North => (right, normal, negate(down)),
+            _ => unreachable!()
+        }
+    }
+
+    /// The compass direction within this face which points along `vector`
+    fn direction_of(&self, vector: Vector) -> Direction {
+        if vector == self.right {
+            Direction::East
+        } else if vector == negate(self.right) {
+            Direction::West
+        } else if vector == self.down {
+            Direction::South
+        } else {
+            Direction::North
+        }
+    }
+}
+
+/// A net of six square faces, such as a puzzle map, folded up into a cube.
+///
+/// Works with any of the 11 possible nets at any size, and any orientation. Once folded,
+/// `wrap()` says where you end up after walking off the edge of a face.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CubeNet {
+    /// Width and height of each face, in tiles
+    pub face_size: i32,
+    faces: Vec<Face>
+}
+
+impl CubeNet {
+    /// Folds the net made up of the supplied tiles. Faces are located relative to the top left
+    /// of the tiles' bounding box.
+    pub fn fold<'a>(tiles: impl IntoIterator<Item = &'a Coordinate<i32>>) -> Result<Self, CubeNetError> {
+        let tiles = tiles.into_iter().copied().collect::<HashSet<_>>();
+        let face_size = ((tiles.len() / 6) as f64).sqrt().round() as i32;
+        if face_size == 0 || (face_size * face_size * 6) as usize != tiles.len() {
+            return Err(CubeNetError::TileCount(tiles.len()));
+        }
+        let min_x = tiles.iter().map(|t| t.x).min().unwrap();
+        let min_y = tiles.iter().map(|t| t.y).min().unwrap();
+
+        let mut corners = HashMap::new();
+        for tile in tiles.iter() {
+            let corner = Coordinate {
+                x: min_x + (tile.x - min_x) / face_size * face_size,
+                y: min_y + (tile.y - min_y) / face_size * face_size
+            };
+            *corners.entry(corner).or_insert(0) += 1;
+        }
+        if let Some(corner) = corners.iter().filter(|(_, count)| **count != face_size * face_size).map(|(c, _)| *c).min_by_key(|c| (c.y, c.x)) {
+            return Err(CubeNetError::Faces { corner: Some(corner) });
+        }
+
+        let start = *corners.keys().min_by_key(|c| (c.y, c.x)).unwrap();
+        let mut faces = vec![Face { corner: start, right: [1, 0, 0], down: [0, 1, 0], normal: [0, 0, 1] }];
+        let mut unvisited = VecDeque::from([0]);
+        while let Some(index) = unvisited.pop_front() {
+            let face = faces[index];
+            for direction in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                let step = Coordinate { x: 0, y: 0 }.neighbour(direction);
+                let corner = Coordinate { x: face.corner.x + step.x * face_size, y: face.corner.y + step.y * face_size };
+                if !corners.contains_key(&corner) || faces.iter().any(|f| f.corner == corner) {
+                    continue;
+                }
+                let (right, down, normal) = face.fold(direction);
+                if faces.iter().any(|f| f.normal == normal) {
+                    return Err(CubeNetError::Overlap);
+                }
+                faces.push(Face { corner, right, down, normal });
+                unvisited.push_back(faces.len() - 1);
+            }
+        }
+        if faces.len() != 6 || corners.len() != 6 {
+            return Err(CubeNetError::Faces { corner: None });
+        }
+        Ok(CubeNet { face_size, faces })
+    }
+
+    /// The top left tile of each face in the net
+    pub fn face_corners(&self) -> Vec<Coordinate<i32>> {
+        self.faces.iter().map(|f| f.corner).collect()
+    }
+
+    fn face_containing(&self, position: &Coordinate<i32>) -> Option<&Face> {
+        self.faces.iter().find(|f| {
+            (f.corner.x..f.corner.x + self.face_size).contains(&position.x) &&
+            (f.corner.y..f.corner.y + self.face_size).contains(&position.y)
+        })
+    }
+
+    /// Takes one step from `position` towards `facing`. Steps within a face (or onto a
+    /// neighbouring face in the net) move as normal; steps off an edge of the net continue onto
+    /// the face which is adjacent once the cube is folded, and return the new facing.
+    ///
+    /// Returns `None` if `position` isn't on the net, or `facing` isn't North, East, South or West.
+    ///
+    /// Example usage, walking off the top of a net shaped like a cross:
+    /// ```
+    /// # use aochelpers::{Coordinate, CubeNet, Direction};
+    /// let net = " #\n###\n #\n #";
+    /// let tiles = net.lines().enumerate()
+    ///     .flat_map(|(y, l)| l.chars().enumerate().filter(|(_, c)| *c == '#').map(move |(x, _)| Coordinate { x: x as i32, y: y as i32 }))
+    ///     .collect::<Vec<_>>();
+    /// let cube = CubeNet::fold(&tiles).unwrap();
+    /// assert_eq!(cube.wrap(Coordinate { x: 1, y: 0 }, Direction::North), Some((Coordinate { x: 1, y: 3 }, Direction::North)));
+    /// assert_eq!(cube.wrap(Coordinate { x: 1, y: 0 }, Direction::East), Some((Coordinate { x: 2, y: 1 }, Direction::South)));
+    /// assert_eq!(cube.wrap(Coordinate { x: 0, y: 0 }, Direction::East), None);
+    /// ```
+    pub fn wrap(&self, position: Coordinate<i32>, facing: Direction) -> Option<(Coordinate<i32>, Direction)> {
+        let face = self.face_containing(&position)?;
+        let out = match facing {
+            Direction::East => face.right,
+            Direction::West => negate(face.right),
+            Direction::South => face.down,
+            Direction::North => negate(face.down),
+            _ => return None
+        };
+        let next = position.neighbour(facing);
+        if self.face_containing(&next).is_some() {
+            return Some((next, facing));
+        }
+        let size = self.face_size;
+        // Work in 3D with the cube centred on the origin, with each tile 2 units wide
+        let (lx, ly) = (position.x - face.corner.x, position.y - face.corner.y);
+        let point = [0, 1, 2].map(|i| face.normal[i] * size + face.right[i] * (2 * lx + 1 - size) + face.down[i] * (2 * ly + 1 - size));
+        let destination = self.faces.iter().find(|f| f.normal == out).unwrap();
+        let heading = negate(face.normal);
+        // Half a tile to the edge, then half a tile down the far side
+        let moved = [0, 1, 2].map(|i| point[i] + out[i] - face.normal[i]);
+        let x = (dot(moved, destination.right) + size - 1) / 2;
+        let y = (dot(moved, destination.down) + size - 1) / 2;
+        Some((Coordinate { x: destination.corner.x + x, y: destination.corner.y + y }, destination.direction_of(heading)))
+    }
+}
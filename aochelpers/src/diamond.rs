@@ -0,0 +1,97 @@
+use std::{collections::BTreeSet, ops::RangeInclusive};
+use crate::{Coordinate, Rectangle};
+
+/// Every point within a given manhattan distance of a centre point, such as the area covered
+/// by a sensor in 2022 day 15.
+///
+/// Rotating the grid by 45°, with `u = x + y` and `v = x - y`, turns a diamond into an
+/// axis-aligned square, which makes its edges easy to work with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Diamond {
+    pub centre: Coordinate<i64>,
+    pub radius: i64
+}
+
+impl Diamond {
+    pub fn new(centre: Coordinate<i64>, radius: i64) -> Self {
+        Diamond { centre, radius }
+    }
+
+    /// The smallest diamond around `centre` which reaches `edge`
+    pub fn reaching(centre: Coordinate<i64>, edge: &Coordinate<i64>) -> Self {
+        Diamond { centre, radius: centre.manhattan_distance(edge) }
+    }
+
+    pub fn contains(&self, point: &Coordinate<i64>) -> bool {
+        self.centre.manhattan_distance(point) <= self.radius
+    }
+
+    /// The range of x values the diamond covers in row `y`, if any
+    pub fn row_slice(&self, y: i64) -> Option<RangeInclusive<i64>> {
+        let half_width = self.radius - (y - self.centre.y).abs();
+        (half_width >= 0).then(|| self.centre.x - half_width..=self.centre.x + half_width)
+    }
+
+    /// The ranges of `u = x + y` and `v = x - y` which the diamond covers
+    pub fn rotated_bounds(&self) -> (RangeInclusive<i64>, RangeInclusive<i64>) {
+        let (u, v) = (self.centre.x + self.centre.y, self.centre.x - self.centre.y);
+        (u - self.radius..=u + self.radius, v - self.radius..=v + self.radius)
+    }
+}
+
+/// The x ranges covered by any of the diamonds in row `y`, merged so that they don't overlap
+/// or touch, in ascending order
+pub fn row_coverage(diamonds: &[Diamond], y: i64) -> Vec<RangeInclusive<i64>> {
+    let mut slices = diamonds.iter().filter_map(|d| d.row_slice(y)).collect::<Vec<_>>();
+    slices.sort_by_key(|s| *s.start());
+    let mut merged: Vec<RangeInclusive<i64>> = Vec::new();
+    for slice in slices {
+        match merged.last_mut() {
+            Some(last) if *slice.start() <= last.end() + 1 => *last = *last.start()..=*slice.end().max(last.end()),
+            _ => merged.push(slice)
+        }
+    }
+    merged
+}
+
+/// Finds points within `bounds` which none of the diamonds cover, without visiting every point.
+///
+/// An uncovered point hemmed in by diamonds lies just outside the edge of at least one diamond
+/// in each diagonal direction, so only the places where those edges cross (or meet the sides
+/// of `bounds`) need checking. This finds any uncovered point which is hemmed in by diamonds and
+/// the sides of `bounds`, such as the lone gap of 2022 day 15; larger uncovered areas are only
+/// represented by the points found on their boundaries.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::{Coordinate, Diamond, Rectangle, uncovered_points};
+/// let diamonds = [(0, 0), (4, 0), (0, 4), (4, 4)].map(|(x, y)| Diamond::new(Coordinate { x, y }, 3));
+/// let bounds = Rectangle::new(Coordinate { x: 0, y: 0 }, Coordinate { x: 4, y: 4 });
+/// assert_eq!(uncovered_points(&diamonds, &bounds), vec![Coordinate { x: 2, y: 2 }]);
+/// ```
+pub fn uncovered_points(diamonds: &[Diamond], bounds: &Rectangle<i64>) -> Vec<Coordinate<i64>> {
+    let mut u_lines = BTreeSet::new();
+    let mut v_lines = BTreeSet::new();
+    for diamond in diamonds {
+        let (u, v) = diamond.rotated_bounds();
+        u_lines.extend([u.start() - 1, u.end() + 1]);
+        v_lines.extend([v.start() - 1, v.end() + 1]);
+    }
+    let (left, right, top, bottom) = (bounds.top_left.x, bounds.bottom_right.x, bounds.top_left.y, bounds.bottom_right.y);
+    let mut candidates = BTreeSet::new();
+    for u in u_lines.iter() {
+        for v in v_lines.iter().filter(|v| (u - *v) % 2 == 0) {
+            candidates.insert(Coordinate { x: (u + v) / 2, y: (u - v) / 2 });
+        }
+        candidates.extend([Coordinate { x: left, y: u - left }, Coordinate { x: right, y: u - right },
+                           Coordinate { x: u - top, y: top }, Coordinate { x: u - bottom, y: bottom }]);
+    }
+    for v in v_lines.iter() {
+        candidates.extend([Coordinate { x: left, y: left - v }, Coordinate { x: right, y: right - v },
+                           Coordinate { x: v + top, y: top }, Coordinate { x: v + bottom, y: bottom }]);
+    }
+    candidates.extend([bounds.top_left, bounds.bottom_right, Coordinate { x: left, y: bottom }, Coordinate { x: right, y: top }]);
+    candidates.into_iter()
+        .filter(|c| bounds.contains(c) && !diamonds.iter().any(|d| d.contains(c)))
+        .collect()
+}
@@ -0,0 +1,264 @@
+use std::{fmt::{self, Display}, error::Error, collections::HashMap, str::FromStr};
+use num::{BigRational, Zero, One};
+use crate::{parse_lines, scan, ParseError};
+
+/// Arithmetic operators which can combine two nodes of an `ExpressionGraph`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide
+}
+
+impl FromStr for Operator {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "+" => Ok(Operator::Add),
+            "-" => Ok(Operator::Subtract),
+            "*" => Ok(Operator::Multiply),
+            "/" => Ok(Operator::Divide),
+            _ => Err(ParseError::new(1, s, "one of +, -, * or /"))
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/"
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// A single named node: either a known value, or an operation on two other nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpressionNode {
+    Value(BigRational),
+    Operation { left: String, operator: Operator, right: String }
+}
+
+/// Returned when an `ExpressionGraph` can't be evaluated or solved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpressionError {
+    /// No node called `name` exists. `referenced_by` is the node which refers to it, if any.
+    Undefined { name: String, referenced_by: Option<String> },
+    /// The nodes in `path` depend on each other in a loop; the first and last entries are the same node
+    Cycle { path: Vec<String> },
+    /// `node` divides by a value which evaluates to zero
+    DivisionByZero { node: String },
+    /// `node` multiplies the unknown by itself or divides by it, so the equation isn't linear
+    NonLinear { node: String },
+    /// The unknown cancels out of the equation, which therefore has no solution or infinitely many
+    NoUniqueSolution
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpressionError::Undefined { name, referenced_by: Some(parent) } => write!(f, "{} refers to undefined node {}", parent, name),
+            ExpressionError::Undefined { name, referenced_by: None } => write!(f, "Undefined node {}", name),
+            ExpressionError::Cycle { path } => write!(f, "Cycle detected: {}", path.join(" -> ")),
+            ExpressionError::DivisionByZero { node } => write!(f, "{} divides by zero", node),
+            ExpressionError::NonLinear { node } => write!(f, "{} is not linear in the unknown", node),
+            ExpressionError::NoUniqueSolution => write!(f, "Equation does not have a unique solution")
+        }
+    }
+}
+
+impl Error for ExpressionError {}
+
+/// A value of the form `coefficient * unknown + constant`, used while solving
+#[derive(Debug, Clone)]
+struct Linear {
+    coefficient: BigRational,
+    constant: BigRational
+}
+
+impl Linear {
+    fn constant(value: BigRational) -> Self {
+        Linear { coefficient: BigRational::zero(), constant: value }
+    }
+
+    fn scale(self, factor: &BigRational) -> Self {
+        Linear { coefficient: self.coefficient * factor, constant: self.constant * factor }
+    }
+}
+
+/// State shared across one evaluation, so that each node is only evaluated once
+struct Walk<V> {
+    memo: HashMap<String, V>,
+    visiting: Vec<String>,
+    substitute: Option<(String, V)>
+}
+
+impl<V> Walk<V> {
+    fn new(substitute: Option<(String, V)>) -> Self {
+        Walk { memo: HashMap::new(), visiting: Vec::new(), substitute }
+    }
+}
+
+/// A set of named nodes, each of which is a value or an arithmetic operation on two other
+/// nodes, such as the monkeys of 2022 day 21 or the wires of a circuit.
+///
+/// Nodes may be shared by any number of operations; each is evaluated at most once.
+/// Arithmetic is carried out on exact rationals, so division never truncates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpressionGraph {
+    pub nodes: HashMap<String, ExpressionNode>
+}
+
+impl ExpressionGraph {
+    pub fn new() -> Self {
+        ExpressionGraph { nodes: HashMap::new() }
+    }
+
+    pub fn insert_value(&mut self, name: &str, value: BigRational) {
+        self.nodes.insert(name.to_string(), ExpressionNode::Value(value));
+    }
+
+    pub fn insert_operation(&mut self, name: &str, left: &str, operator: Operator, right: &str) {
+        self.nodes.insert(name.to_string(), ExpressionNode::Operation { left: left.to_string(), operator, right: right.to_string() });
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ExpressionNode> {
+        self.nodes.get(name)
+    }
+
+    /// The names of the two nodes combined by `name`, if it is an operation
+    pub fn operands(&self, name: &str) -> Option<(&str, &str)> {
+        match self.nodes.get(name) {
+            Some(ExpressionNode::Operation { left, right, .. }) => Some((left, right)),
+            _ => None
+        }
+    }
+
+    fn walk<V: Clone>(&self, name: &str, referenced_by: Option<&str>, walk: &mut Walk<V>,
+            leaf: &impl Fn(&BigRational) -> V,
+            combine: &impl Fn(&str, V, Operator, V) -> Result<V, ExpressionError>) -> Result<V, ExpressionError> {
+        if let Some((substituted, value)) = &walk.substitute {
+            if substituted == name {
+                return Ok(value.clone());
+            }
+        }
+        if let Some(value) = walk.memo.get(name) {
+            return Ok(value.clone());
+        }
+        if let Some(start) = walk.visiting.iter().position(|n| n == name) {
+            let mut path = walk.visiting[start..].to_vec();
+            path.push(name.to_string());
+            return Err(ExpressionError::Cycle { path });
+        }
+        let node = self.nodes.get(name).ok_or_else(|| ExpressionError::Undefined {
+            name: name.to_string(),
+            referenced_by: referenced_by.map(|r| r.to_string())
+        })?;
+        let value = match node {
+            ExpressionNode::Value(value) => leaf(value),
+            ExpressionNode::Operation { left, operator, right } => {
+                walk.visiting.push(name.to_string());
+                let left = self.walk(left, Some(name), walk, leaf, combine)?;
+                let right = self.walk(right, Some(name), walk, leaf, combine)?;
+                walk.visiting.pop();
+                combine(name, left, *operator, right)?
+            }
+        };
+        walk.memo.insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+
+    fn apply(name: &str, left: BigRational, operator: Operator, right: BigRational) -> Result<BigRational, ExpressionError> {
+        Ok(match operator {
+            Operator::Add => left + right,
+            Operator::Subtract => left - right,
+            Operator::Multiply => left * right,
+            Operator::Divide if right.is_zero() => return Err(ExpressionError::DivisionByZero { node: name.to_string() }),
+            Operator::Divide => left / right
+        })
+    }
+
+    /// Evaluates the named node
+    pub fn evaluate(&self, name: &str) -> Result<BigRational, ExpressionError> {
+        self.walk(name, None, &mut Walk::new(None), &|v| v.clone(), &Self::apply)
+    }
+
+    /// Evaluates every node in the graph
+    pub fn evaluate_all(&self) -> Result<HashMap<String, BigRational>, ExpressionError> {
+        let mut walk = Walk::new(None);
+        for name in self.nodes.keys() {
+            self.walk(name, None, &mut walk, &|v| v.clone(), &Self::apply)?;
+        }
+        Ok(walk.memo)
+    }
+
+    /// Finds the value of `unknown` which makes nodes `left` and `right` evaluate to the same value.
+    /// Any existing definition of `unknown` is ignored, so it needn't be in the graph at all.
+    ///
+    /// The unknown may appear any number of times, provided the equation is linear in it: it
+    /// can't be multiplied by itself, or appear in a divisor.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use aochelpers::ExpressionGraph;
+    /// # use num::{BigRational, BigInt};
+    /// let graph = "total: x * three\nthree: 3\ntarget: 7".parse::<ExpressionGraph>().unwrap();
+    /// let x = graph.solve("x", "total", "target").unwrap();
+    /// assert_eq!(x, BigRational::new(BigInt::from(7), BigInt::from(3)));
+    /// ```
+    pub fn solve(&self, unknown: &str, left: &str, right: &str) -> Result<BigRational, ExpressionError> {
+        let x = Linear { coefficient: BigRational::one(), constant: BigRational::zero() };
+        let mut walk = Walk::new(Some((unknown.to_string(), x)));
+        let combine = |name: &str, l: Linear, operator: Operator, r: Linear| -> Result<Linear, ExpressionError> {
+            let non_linear = || ExpressionError::NonLinear { node: name.to_string() };
+            match operator {
+                Operator::Add => Ok(Linear { coefficient: l.coefficient + r.coefficient, constant: l.constant + r.constant }),
+                Operator::Subtract => Ok(Linear { coefficient: l.coefficient - r.coefficient, constant: l.constant - r.constant }),
+                Operator::Multiply if l.coefficient.is_zero() => Ok(r.scale(&l.constant)),
+                Operator::Multiply if r.coefficient.is_zero() => Ok(l.scale(&r.constant)),
+                Operator::Multiply => Err(non_linear()),
+                Operator::Divide if !r.coefficient.is_zero() => Err(non_linear()),
+                Operator::Divide if r.constant.is_zero() => Err(ExpressionError::DivisionByZero { node: name.to_string() }),
+                Operator::Divide => Ok(l.scale(&r.constant.recip()))
+            }
+        };
+        let left = self.walk(left, None, &mut walk, &|v| Linear::constant(v.clone()), &combine)?;
+        let right = self.walk(right, None, &mut walk, &|v| Linear::constant(v.clone()), &combine)?;
+        let coefficient = left.coefficient - right.coefficient;
+        if coefficient.is_zero() {
+            return Err(ExpressionError::NoUniqueSolution);
+        }
+        Ok((right.constant - left.constant) / coefficient)
+    }
+}
+
+/// Parses one node per line, in the form `name: 5` or `name: left + right`. Values may be
+/// integers or fractions such as `3/4`. Each name may only be defined once.
+impl FromStr for ExpressionGraph {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let nodes = parse_lines(s, |line| -> Result<(String, ExpressionNode), ParseError> {
+            if line.split_whitespace().count() > 2 {
+                let (name, left, operator, right) = scan!("{}: {} {} {}", line => String, String, Operator, String)?;
+                Ok((name, ExpressionNode::Operation { left, operator, right }))
+            } else {
+                let (name, value) = scan!("{}: {}", line => String, BigRational)?;
+                Ok((name, ExpressionNode::Value(value)))
+            }
+        })?;
+        let mut graph = HashMap::new();
+        for ((name, node), (index, line)) in nodes.into_iter().zip(s.lines().enumerate()) {
+            if graph.contains_key(&name) {
+                let fragment = &line[line.find(name.as_str()).unwrap()..][..name.len()];
+                return Err(ParseError::at(line, fragment, "a node name which hasn't already been defined").on_line(index + 1));
+            }
+            graph.insert(name, node);
+        }
+        Ok(ExpressionGraph { nodes: graph })
+    }
+}
@@ -0,0 +1,228 @@
+use std::{collections::BTreeMap, fmt::{self, Display}, error::Error};
+use crate::ParseError;
+
+/// Returned when a `FileSystem` can't be changed as requested. Each variant holds the
+/// absolute path of the entry concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FsError {
+    /// The entry is a file, so nothing can be created inside it
+    NotADirectory(String),
+    /// A directory can't be created because a file already has its name
+    FileExists(String),
+    /// A file can't be created because a directory already has its name
+    DirectoryExists(String)
+}
+
+impl Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsError::NotADirectory(path) => write!(f, "{} is a file, not a directory", path),
+            FsError::FileExists(path) => write!(f, "Can't create directory {}: a file has that name", path),
+            FsError::DirectoryExists(path) => write!(f, "Can't create file {}: a directory has that name", path)
+        }
+    }
+}
+
+impl Error for FsError {}
+
+/// A file or directory within a `FileSystem`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsEntry {
+    pub name: String,
+    /// Index of the enclosing directory; `None` for the root
+    pub parent: Option<usize>,
+    /// Directories have `Some` map of child names to indices; files have `None`
+    pub children: Option<BTreeMap<String, usize>>,
+    /// For files, the file size. For directories, the total size of everything beneath them.
+    pub size: u64
+}
+
+impl FsEntry {
+    pub fn is_dir(&self) -> bool {
+        self.children.is_some()
+    }
+}
+
+/// A directory tree stored as an arena of entries with parent links, so that entries can be
+/// referred to by index and walked in either direction.
+///
+/// Directory sizes are kept up to date as files are added, so size queries are cheap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSystem {
+    pub entries: Vec<FsEntry>
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileSystem {
+    /// Index of the root directory
+    pub const ROOT: usize = 0;
+
+    /// Creates a filesystem containing only an empty root directory
+    pub fn new() -> Self {
+        FileSystem { entries: vec![FsEntry { name: String::new(), parent: None, children: Some(BTreeMap::new()), size: 0 }] }
+    }
+
+    pub fn entry(&self, index: usize) -> &FsEntry {
+        &self.entries[index]
+    }
+
+    fn child(&self, directory: usize, name: &str) -> Option<usize> {
+        self.entries[directory].children.as_ref()?.get(name).copied()
+    }
+
+    /// The absolute path `name` would have inside `directory`
+    fn path_within(&self, directory: usize, name: &str) -> String {
+        match directory {
+            Self::ROOT => format!("/{}", name),
+            _ => format!("{}/{}", self.path(directory), name)
+        }
+    }
+
+    fn insert(&mut self, directory: usize, name: &str, entry: FsEntry) -> Result<usize, FsError> {
+        if !self.entries[directory].is_dir() {
+            return Err(FsError::NotADirectory(self.path(directory)));
+        }
+        let index = self.entries.len();
+        self.entries.push(entry);
+        self.entries[directory].children.as_mut().unwrap().insert(name.to_string(), index);
+        Ok(index)
+    }
+
+    /// Creates a subdirectory, returning its index. If it already exists, the existing
+    /// directory is returned instead. Fails if `directory` is a file, or a file called `name`
+    /// already exists.
+    pub fn mkdir(&mut self, directory: usize, name: &str) -> Result<usize, FsError> {
+        match self.child(directory, name) {
+            Some(existing) if self.entries[existing].is_dir() => Ok(existing),
+            Some(_) => Err(FsError::FileExists(self.path_within(directory, name))),
+            None => self.insert(directory, name, FsEntry { name: name.to_string(), parent: Some(directory), children: Some(BTreeMap::new()), size: 0 })
+        }
+    }
+
+    /// Adds a file to a directory, returning its index. Adding a file which already exists
+    /// replaces its size, so listing the same directory twice doesn't count files twice.
+    /// Fails if `directory` is a file, or a directory called `name` already exists.
+    pub fn add_file(&mut self, directory: usize, name: &str, size: u64) -> Result<usize, FsError> {
+        let (index, previous) = match self.child(directory, name) {
+            Some(existing) if self.entries[existing].is_dir() => return Err(FsError::DirectoryExists(self.path_within(directory, name))),
+            Some(existing) => (existing, self.entries[existing].size),
+            None => (self.insert(directory, name, FsEntry { name: name.to_string(), parent: Some(directory), children: None, size: 0 })?, 0)
+        };
+        self.entries[index].size = size;
+        let mut ancestor = Some(directory);
+        while let Some(current) = ancestor {
+            self.entries[current].size = self.entries[current].size - previous + size;
+            ancestor = self.entries[current].parent;
+        }
+        Ok(index)
+    }
+
+    /// The absolute path of an entry, such as `/a/e/i`
+    pub fn path(&self, index: usize) -> String {
+        let mut names = Vec::new();
+        let mut current = Some(index);
+        while let Some(i) = current {
+            names.push(self.entries[i].name.as_str());
+            current = self.entries[i].parent;
+        }
+        if names.len() == 1 {
+            return "/".to_string();
+        }
+        names.iter().rev().cloned().collect::<Vec<_>>().join("/")
+    }
+
+    /// Looks up an entry by path. Relative paths are taken from `directory`; `..` moves up a level.
+    pub fn find(&self, directory: usize, path: &str) -> Option<usize> {
+        let start = if path.starts_with('/') { Self::ROOT } else { directory };
+        path.split('/').filter(|p| !p.is_empty() && *p != ".").try_fold(start, |current, name| match name {
+            ".." => self.entries[current].parent,
+            name => self.child(current, name)
+        })
+    }
+
+    /// Indices of every directory, including the root
+    pub fn directories(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.entries.len()).filter(|i| self.entries[*i].is_dir())
+    }
+
+    /// Every directory whose total size is at most `limit`
+    pub fn directories_with_size_at_most(&self, limit: u64) -> Vec<usize> {
+        self.directories().filter(|d| self.entries[*d].size <= limit).collect()
+    }
+
+    /// The smallest directory whose total size is at least `minimum`, e.g. the best candidate
+    /// for deletion to free up `minimum` bytes
+    pub fn smallest_directory_of_at_least(&self, minimum: u64) -> Option<usize> {
+        self.directories().filter(|d| self.entries[*d].size >= minimum).min_by_key(|d| self.entries[*d].size)
+    }
+
+    /// Builds a filesystem by replaying a shell transcript of `$ cd <dir>` and `$ ls` commands,
+    /// along with the output of `ls` (`dir <name>` or `<size> <name>` lines).
+    ///
+    /// `cd` accepts absolute or relative paths and creates directories which haven't been
+    /// listed yet. Errors give the line number of the offending transcript line, including
+    /// lines which give a file and a directory the same name.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use aochelpers::FileSystem;
+    /// let fs = FileSystem::from_transcript("$ cd /\n$ ls\ndir a\n10 b.txt\n$ cd a\n$ ls\n5 c").unwrap();
+    /// assert_eq!(fs.entry(FileSystem::ROOT).size, 15);
+    /// let a = fs.find(FileSystem::ROOT, "/a").unwrap();
+    /// assert_eq!((fs.path(a), fs.entry(a).size), ("/a".to_string(), 5));
+    /// ```
+    pub fn from_transcript(transcript: &str) -> Result<Self, ParseError> {
+        let mut fs = FileSystem::new();
+        let mut cwd = Self::ROOT;
+        let mut listing = false;
+        for (i, line) in transcript.lines().enumerate() {
+            let fail = |fragment: &str, expected: &str| ParseError::at(line, fragment, expected).on_line(i + 1);
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(command) = trimmed.strip_prefix('$') {
+                let mut words = command.split_whitespace();
+                listing = false;
+                match (words.next(), words.next(), words.next()) {
+                    (Some("ls"), None, _) => listing = true,
+                    (Some("cd"), Some(path), None) => {
+                        cwd = match path {
+                            "/" => Self::ROOT,
+                            ".." => fs.entries[cwd].parent.ok_or_else(|| fail(path, "a directory below the root"))?,
+                            _ => {
+                                let start = if path.starts_with('/') { Self::ROOT } else { cwd };
+                                let mut directory = start;
+                                for name in path.split('/').filter(|p| !p.is_empty()) {
+                                    directory = match name {
+                                        ".." => fs.entries[directory].parent.ok_or_else(|| fail(path, "a directory below the root"))?,
+                                        "." => directory,
+                                        name => fs.mkdir(directory, name).map_err(|_| fail(path, "a directory rather than a file"))?
+                                    };
+                                }
+                                directory
+                            }
+                        };
+                    }
+                    _ => return Err(fail(command.trim(), "`cd <directory>` or `ls`"))
+                }
+            } else if !listing {
+                return Err(fail(trimmed, "a command starting with `$`"));
+            } else if let Some(name) = trimmed.strip_prefix("dir ") {
+                let name = name.trim();
+                fs.mkdir(cwd, name).map_err(|_| fail(name, "a name not already used by a file"))?;
+            } else {
+                let (size, name) = trimmed.split_once(char::is_whitespace).ok_or_else(|| fail(trimmed, "`dir <name>` or `<size> <name>`"))?;
+                let size = size.parse().map_err(|_| fail(size, "a file size"))?;
+                let name = name.trim();
+                fs.add_file(cwd, name, size).map_err(|_| fail(name, "a name not already used by a directory"))?;
+            }
+        }
+        Ok(fs)
+    }
+}
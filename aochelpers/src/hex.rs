@@ -0,0 +1,284 @@
+use std::{fmt::{self, Display}, ops::{Add, Sub, AddAssign, SubAssign}, str::FromStr};
+use num::{Integer, Signed};
+use anyhow::anyhow;
+use crate::Coordinate;
+
+/// The six directions leading out of a hexagon on a "pointy-topped" hex grid, where
+/// rows of hexagons are offset horizontally from one another:
+/// ```text
+///   NW NE
+///  W  X  E
+///   SW SE
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast
+}
+
+impl HexDirection {
+    /// All six directions, in anticlockwise order starting from `East`
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::East,
+        HexDirection::NorthEast,
+        HexDirection::NorthWest,
+        HexDirection::West,
+        HexDirection::SouthWest,
+        HexDirection::SouthEast
+    ];
+
+    /// The direction reached by turning 60° clockwise
+    pub fn rotate_clockwise(&self) -> Self {
+        self.rotate(-1)
+    }
+
+    /// The direction reached by turning 60° anticlockwise
+    pub fn rotate_anticlockwise(&self) -> Self {
+        self.rotate(1)
+    }
+
+    /// The direction reached by turning `steps` multiples of 60°. Positive values turn
+    /// anticlockwise, negative values turn clockwise.
+    pub fn rotate(&self, steps: i32) -> Self {
+        let index = Self::ALL.iter().position(|d| d == self).unwrap() as i32;
+        Self::ALL[(index + steps).rem_euclid(6) as usize]
+    }
+
+    /// The direction pointing the opposite way
+    pub fn opposite(&self) -> Self {
+        self.rotate(3)
+    }
+}
+
+impl FromStr for HexDirection {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, anyhow::Error> {
+        match s.to_ascii_lowercase().as_str() {
+            "e" => Ok(HexDirection::East),
+            "ne" => Ok(HexDirection::NorthEast),
+            "nw" => Ok(HexDirection::NorthWest),
+            "w" => Ok(HexDirection::West),
+            "sw" => Ok(HexDirection::SouthWest),
+            "se" => Ok(HexDirection::SouthEast),
+            _ => Err(anyhow!("Unrecognised hex direction: {}", s))
+        }
+    }
+}
+
+/// Renders the direction in its abbreviated `ne` / `w` form
+impl Display for HexDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            HexDirection::East => "e",
+            HexDirection::NorthEast => "ne",
+            HexDirection::NorthWest => "nw",
+            HexDirection::West => "w",
+            HexDirection::SouthWest => "sw",
+            HexDirection::SouthEast => "se",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Parses a sequence of hex steps such as `"esenee"` or `"ne,ne,sw"` to a list of `HexDirection`s.
+///
+/// Steps may be run together or separated by commas and / or whitespace.
+pub fn parse_hex_steps(data: &str) -> Result<Vec<HexDirection>, anyhow::Error> {
+    let mut steps = Vec::new();
+    let mut chars = data.chars().enumerate();
+    while let Some((pos, c)) = chars.next() {
+        match c.to_ascii_lowercase() {
+            'e' => steps.push(HexDirection::East),
+            'w' => steps.push(HexDirection::West),
+            'n' | 's' => {
+                let step = match chars.next() {
+                    Some((_, second)) => format!("{}{}", c, second).parse::<HexDirection>(),
+                    None => Err(anyhow!("Unexpected end of input after '{}'", c))
+                };
+                steps.push(step.map_err(|e| anyhow!("{} at position {}", e, pos))?);
+            }
+            ',' => {},
+            c if c.is_whitespace() => {},
+            _ => return Err(anyhow!("Unrecognised hex direction {} at position {}", c, pos))
+        }
+    }
+    Ok(steps)
+}
+
+/// A position on a hex grid, expressed in axial `(q, r)` coordinates.
+///
+/// The third cube coordinate `s` is implied by the constraint `q + r + s == 0`.
+/// `q` increases towards the east, `r` increases towards the south-east (and thus
+/// down the page):
+/// ```text
+///    (0,-1) (1,-1)
+/// (-1,0) (0,0) (1,0)
+///    (-1,1) (0,1)
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct HexCoordinate<T> {
+    pub q: T,
+    pub r: T
+}
+
+/// Renders HexCoordinate as `(q,r)`
+impl<T: Display> Display for HexCoordinate<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({},{})", self.q, self.r)
+    }
+}
+
+impl<T: Add<Output = T>> Add for HexCoordinate<T> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            q: self.q + other.q,
+            r: self.r + other.r
+        }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for HexCoordinate<T> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self {
+            q: self.q - other.q,
+            r: self.r - other.r
+        }
+    }
+}
+
+impl<T: AddAssign> AddAssign for HexCoordinate<T> {
+    fn add_assign(&mut self, other: Self) {
+        self.q += other.q;
+        self.r += other.r;
+    }
+}
+
+impl<T: SubAssign> SubAssign for HexCoordinate<T> {
+    fn sub_assign(&mut self, other: Self) {
+        self.q -= other.q;
+        self.r -= other.r;
+    }
+}
+
+impl<T: Integer + Signed + Copy> HexCoordinate<T> {
+    /// Builds a `HexCoordinate` from cube coordinates. Returns `None` if `q + r + s != 0`.
+    pub fn from_cube(q: T, r: T, s: T) -> Option<Self> {
+        if q + r + s == num::zero() {
+            Some(HexCoordinate { q, r })
+        } else {
+            None
+        }
+    }
+
+    /// The implied third cube coordinate
+    pub fn s(&self) -> T {
+        -self.q - self.r
+    }
+
+    /// The cube representation `(q, r, s)` of this coordinate
+    pub fn cube(&self) -> (T, T, T) {
+        (self.q, self.r, self.s())
+    }
+
+    /// Converts a `Coordinate` on the doubled-width layout used by `Coordinate::hex_neighbours`
+    /// to axial coordinates. `x + y` is expected to be even.
+    pub fn from_doubled(coordinate: Coordinate<T>) -> Self {
+        HexCoordinate { q: (coordinate.x - coordinate.y) / (num::one::<T>() + num::one()), r: coordinate.y }
+    }
+
+    /// Converts to a `Coordinate` on the doubled-width layout used by `Coordinate::hex_neighbours`
+    pub fn to_doubled(&self) -> Coordinate<T> {
+        Coordinate { x: self.q + self.q + self.r, y: self.r }
+    }
+
+    /// The unit offset for a single step in the given `HexDirection`
+    pub fn unit(direction: HexDirection) -> Self {
+        let (q, r): (T, T) = match direction {
+            HexDirection::East => (num::one(), num::zero()),
+            HexDirection::NorthEast => (num::one(), -num::one::<T>()),
+            HexDirection::NorthWest => (num::zero(), -num::one::<T>()),
+            HexDirection::West => (-num::one::<T>(), num::zero()),
+            HexDirection::SouthWest => (-num::one::<T>(), num::one()),
+            HexDirection::SouthEast => (num::zero(), num::one()),
+        };
+        HexCoordinate { q, r }
+    }
+
+    /// The neighbouring `HexCoordinate` in the supplied `HexDirection`
+    pub fn neighbour(&self, direction: HexDirection) -> Self {
+        *self + Self::unit(direction)
+    }
+
+    /// All six neighbouring hexes, in the order given by `HexDirection::ALL`
+    pub fn neighbours(&self) -> Vec<Self> {
+        HexDirection::ALL.iter().map(|d| self.neighbour(*d)).collect()
+    }
+
+    /// The number of steps needed to travel between two hexes
+    pub fn distance(&self, other: &Self) -> T {
+        let delta = *self - *other;
+        (delta.q.abs() + delta.r.abs() + delta.s().abs()) / (num::one::<T>() + num::one())
+    }
+
+    /// The hex reached by starting here and following each of the supplied steps in turn
+    pub fn walk<'a>(&self, steps: impl IntoIterator<Item = &'a HexDirection>) -> Self {
+        steps.into_iter().fold(*self, |position, step| position.neighbour(*step))
+    }
+
+    /// Rotates this coordinate by `steps` multiples of 60° around the origin. Positive values
+    /// rotate anticlockwise, negative values rotate clockwise.
+    pub fn rotate(&self, steps: i32) -> Self {
+        let mut result = *self;
+        for _ in 0..steps.rem_euclid(6) {
+            // Anticlockwise by 60°: (q, r, s) -> (-s, -q, -r)
+            result = HexCoordinate { q: -result.s(), r: -result.q };
+        }
+        result
+    }
+
+    /// Rotates this coordinate by `steps` multiples of 60° around the supplied centre.
+    pub fn rotate_around(&self, centre: &Self, steps: i32) -> Self {
+        (*self - *centre).rotate(steps) + *centre
+    }
+
+    /// All hexes exactly `radius` steps away, walking anticlockwise from the hex `radius`
+    /// steps to the south-east. A radius of zero returns this hex alone.
+    pub fn ring(&self, radius: T) -> Vec<Self> {
+        if radius == num::zero() {
+            return vec![*self];
+        }
+        let mut results = Vec::new();
+        let mut position = *self;
+        let mut count = num::zero::<T>();
+        while count < radius {
+            position = position.neighbour(HexDirection::SouthEast);
+            count = count + num::one();
+        }
+        for direction in HexDirection::ALL.iter().map(|d| d.rotate(1)) {
+            let mut count = num::zero::<T>();
+            while count < radius {
+                results.push(position);
+                position = position.neighbour(direction);
+                count = count + num::one();
+            }
+        }
+        results
+    }
+
+    /// All hexes within `radius` steps, ordered by increasing distance, starting with this hex.
+    pub fn spiral(&self, radius: T) -> Vec<Self> {
+        let mut results = Vec::new();
+        let mut current = num::zero::<T>();
+        while current <= radius {
+            results.extend(self.ring(current));
+            current = current + num::one();
+        }
+        results
+    }
+}
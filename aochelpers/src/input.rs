@@ -0,0 +1,150 @@
+use std::{path::Path, str::FromStr, fmt::Display};
+use anyhow::{anyhow, Context};
+
+/// Tidies up puzzle input so that it parses the same way regardless of where it came from:
+/// Windows (`\r\n`) line endings become `\n`, trailing whitespace is removed from every line,
+/// and trailing blank lines are removed. Leading whitespace is significant in some puzzles,
+/// so is left alone.
+pub fn normalise_input(data: &str) -> String {
+    let lines = data.lines().map(|line| line.trim_end()).collect::<Vec<_>>();
+    let end = lines.iter().rposition(|line| !line.is_empty()).map(|i| i + 1).unwrap_or(0);
+    lines[..end].join("\n")
+}
+
+/// Reads a puzzle input file, normalising it with `normalise_input()`
+pub fn read_input(path: impl AsRef<Path>) -> std::io::Result<String> {
+    Ok(normalise_input(&std::fs::read_to_string(path)?))
+}
+
+/// Splits input into sections separated by one or more blank lines, normalising line endings
+/// and trailing whitespace along the way. Lines containing only whitespace count as blank.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::split_sections;
+/// assert_eq!(split_sections("1\r\n2\r\n\r\n3  \n\n\n"), vec!["1\n2", "3"]);
+/// ```
+pub fn split_sections(data: &str) -> Vec<String> {
+    numbered_sections(data).into_iter().map(|(_, section)| section).collect()
+}
+
+/// Splits input into sections in the same way as `split_sections()`, along with the 1-based
+/// line of the input on which each section starts, so errors can point back at the input.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::numbered_sections;
+/// let sections = numbered_sections("\r\n1\r\n2\r\n\r\n  \r\n3\r\n");
+/// assert_eq!(sections, vec![(2, "1\n2".to_string()), (6, "3".to_string())]);
+/// ```
+pub fn numbered_sections(data: &str) -> Vec<(usize, String)> {
+    let mut sections: Vec<(usize, String)> = Vec::new();
+    let mut after_blank = true;
+    for (index, line) in normalise_input(data).lines().enumerate() {
+        match (line.is_empty(), sections.last_mut()) {
+            (true, _) => {},
+            (false, Some((_, section))) if !after_blank => {
+                section.push('\n');
+                section.push_str(line);
+            },
+            (false, _) => sections.push((index + 1, line.to_string()))
+        }
+        after_blank = line.is_empty();
+    }
+    sections
+}
+
+/// A type which can be built from a single blank-line-separated section of puzzle input.
+///
+/// `Vec<T>` parses each line of the section as a `T`, while `String` and the numeric primitives
+/// parse the whole section. Types which already implement `FromStr` can opt in with a one-line
+/// implementation:
+/// ```
+/// # use aochelpers::FromSection;
+/// # #[derive(Debug)]
+/// # struct Header;
+/// # impl std::str::FromStr for Header {
+/// #     type Err = std::convert::Infallible;
+/// #     fn from_str(_: &str) -> Result<Self, Self::Err> { Ok(Header) }
+/// # }
+/// impl FromSection for Header {
+///     fn from_section(section: &str) -> Result<Self, anyhow::Error> {
+///         Ok(section.parse()?)
+///     }
+/// }
+/// ```
+pub trait FromSection: Sized {
+    fn from_section(section: &str) -> Result<Self, anyhow::Error>;
+}
+
+impl<T: FromStr> FromSection for Vec<T> where <T as FromStr>::Err: Display {
+    fn from_section(section: &str) -> Result<Self, anyhow::Error> {
+        section.lines().enumerate()
+            .map(|(i, line)| line.parse::<T>().map_err(|e| anyhow!("line {}: {} ({:?})", i + 1, e, line)))
+            .collect()
+    }
+}
+
+impl FromSection for String {
+    fn from_section(section: &str) -> Result<Self, anyhow::Error> {
+        Ok(section.to_string())
+    }
+}
+
+macro_rules! from_section_via_from_str {
+    ($($t:ty),*) => {
+        $(impl FromSection for $t {
+            fn from_section(section: &str) -> Result<Self, anyhow::Error> {
+                Ok(section.trim().parse()?)
+            }
+        })*
+    };
+}
+
+from_section_via_from_str!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// A type which can be built from all of the sections in a puzzle input: either a tuple with
+/// one `FromSection` element per section, or a `Vec` of identically-typed sections.
+pub trait FromSections: Sized {
+    fn from_sections(sections: &[String]) -> Result<Self, anyhow::Error>;
+}
+
+impl<T: FromSection> FromSections for Vec<T> {
+    fn from_sections(sections: &[String]) -> Result<Self, anyhow::Error> {
+        sections.iter().enumerate()
+            .map(|(i, s)| T::from_section(s).with_context(|| format!("Couldn't parse section {}", i + 1)))
+            .collect()
+    }
+}
+
+macro_rules! from_sections_for_tuple {
+    ($count:expr; $($t:ident $i:tt),*) => {
+        impl<$($t: FromSection),*> FromSections for ($($t,)*) {
+            fn from_sections(sections: &[String]) -> Result<Self, anyhow::Error> {
+                if sections.len() != $count {
+                    return Err(anyhow!("Expected {} sections, found {}", $count, sections.len()));
+                }
+                Ok(($($t::from_section(&sections[$i]).with_context(|| format!("Couldn't parse section {}", $i + 1))?,)*))
+            }
+        }
+    };
+}
+
+from_sections_for_tuple!(1; A 0);
+from_sections_for_tuple!(2; A 0, B 1);
+from_sections_for_tuple!(3; A 0, B 1, C 2);
+from_sections_for_tuple!(4; A 0, B 1, C 2, D 3);
+
+/// Splits input into blank-line-separated sections and parses them into the requested type.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::sections;
+/// let (total, values) = sections::<(i32, Vec<i32>)>("6\n\n1\n2\n3\n").unwrap();
+/// assert_eq!(total, values.iter().sum());
+/// let groups = sections::<Vec<Vec<i32>>>("1\n2\n\n3").unwrap();
+/// assert_eq!(groups, vec![vec![1, 2], vec![3]]);
+/// ```
+pub fn sections<T: FromSections>(data: &str) -> Result<T, anyhow::Error> {
+    T::from_sections(&split_sections(data))
+}
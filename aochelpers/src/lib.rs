@@ -1,7 +1,54 @@
-use std::{fmt::{Debug,Display, self}, ops::{Add, Sub, AddAssign, SubAssign}, hash::Hash, cmp::Ordering, str::FromStr, fs::{self, File}, env, io::{Write, ErrorKind}, path::PathBuf, error::Error, collections::HashMap};
-use num::Integer;
+use std::{fmt::{Debug,Display, self}, ops::{Add, Sub, AddAssign, SubAssign}, hash::Hash, cmp::Ordering, str::FromStr, fs::{self, File}, env, io::Write, path::PathBuf, error::Error, collections::HashMap};
+use num::{Integer, Signed, CheckedAdd, CheckedSub};
 use log::warn;
 
+mod hex;
+mod coordinate_n;
+mod polygon;
+mod ocr;
+mod render;
+mod record;
+mod input;
+mod scan;
+mod parse_error;
+mod nested;
+mod expression;
+mod vm;
+mod filesystem;
+mod cube;
+mod topology;
+mod circular;
+mod stacks;
+mod monkeys;
+mod sand;
+mod diamond;
+mod valves;
+mod tetris;
+mod production;
+pub use hex::*;
+pub use coordinate_n::*;
+pub use polygon::*;
+pub use ocr::*;
+pub use render::*;
+pub use record::*;
+pub use input::*;
+pub use scan::*;
+pub use parse_error::*;
+pub use nested::*;
+pub use expression::*;
+pub use vm::*;
+pub use filesystem::*;
+pub use cube::*;
+pub use topology::*;
+pub use circular::*;
+pub use stacks::*;
+pub use monkeys::*;
+pub use sand::*;
+pub use diamond::*;
+pub use valves::*;
+pub use tetris::*;
+pub use production::*;
+
 /// Compass directions
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Direction {
@@ -15,6 +62,31 @@ pub enum Direction {
     West
 }
 
+impl Direction {
+    /// All eight directions, clockwise from NorthWest
+    pub const ALL: [Direction; 8] = [Direction::NorthWest, Direction::North, Direction::NorthEast, Direction::East,
+                                     Direction::SouthEast, Direction::South, Direction::SouthWest, Direction::West];
+
+    fn turn(&self, eighths: usize) -> Self {
+        let index = Direction::ALL.iter().position(|d| d == self).unwrap();
+        Direction::ALL[(index + eighths) % 8]
+    }
+
+    /// The direction 90 degrees clockwise of this one (a right turn)
+    pub fn rotate_clockwise(&self) -> Self {
+        self.turn(2)
+    }
+
+    /// The direction 90 degrees anticlockwise of this one (a left turn)
+    pub fn rotate_anticlockwise(&self) -> Self {
+        self.turn(6)
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.turn(4)
+    }
+}
+
 /// Defines a direction used by a Particle. May be either a compass `Direction` or an `i32` bearing.
 pub trait Heading {}
 
@@ -101,15 +173,27 @@ impl<T: Copy + 'static> Coordinate<T> {
 impl<T: Integer + Copy> Coordinate<T> {
     /// All co-ordinates directly neighbouring the square on a grid, excluding diagonals
     pub fn neighbours(&self) -> Vec<Self> {
-        vec![ Coordinate{x: self.x - num::one(), y: self.y},
-              Coordinate{x: self.x + num::one(), y: self.y},
-              Coordinate{x: self.x, y: self.y - num::one()},
-              Coordinate{x: self.x, y: self.y + num::one()},
+        self.neighbours_array().to_vec()
+    }
+    /// All co-ordinates directly neighbouring the square on a grid, excluding diagonals.
+    /// 
+    /// Unlike `neighbours()`, this does not allocate.
+    pub fn neighbours_array(&self) -> [Self; 4] {
+        [ Coordinate{x: self.x - num::one(), y: self.y},
+          Coordinate{x: self.x + num::one(), y: self.y},
+          Coordinate{x: self.x, y: self.y - num::one()},
+          Coordinate{x: self.x, y: self.y + num::one()},
         ]
     }
     /// All co-ordinates directly neighbouring the square on a grid, including diagonals
     pub fn extended_neighbours(&self) -> Vec<Self> {
-        vec![ 
+        self.extended_neighbours_array().to_vec()
+    }
+    /// All co-ordinates directly neighbouring the square on a grid, including diagonals.
+    /// 
+    /// Unlike `extended_neighbours()`, this does not allocate.
+    pub fn extended_neighbours_array(&self) -> [Self; 8] {
+        [ 
             Coordinate{x: self.x - num::one(), y: self.y - num::one()},
             Coordinate{x: self.x - num::one(), y: self.y },
             Coordinate{x: self.x - num::one(), y: self.y + num::one()},
@@ -127,19 +211,62 @@ impl<T: Integer + Copy> Coordinate<T> {
     ///   5 6
     /// ```
     pub fn hex_neighbours(&self) -> Vec<Self> {
-        vec![
+        self.hex_neighbours_array().to_vec()
+    }
+    /// Returns all co-ordinates directly neighbouring the square on an alternating hex grid,
+    /// in the same order as `hex_neighbours()`, without allocating. Like `hex_neighbours()`
+    /// this underflows near zero on unsigned types; use `checked_hex_neighbours()` there.
+    pub fn hex_neighbours_array(&self) -> [Self; 6] {
+        [
             Coordinate{x: self.x - num::one() - num::one(), y: self.y}, 
             Coordinate{x: self.x + num::one() + num::one(), y: self.y}, 
             Coordinate{x: self.x + num::one(), y: self.y - num::one()}, 
             Coordinate{x: self.x + num::one(), y: self.y + num::one()},
             Coordinate{x: self.x - num::one(), y: self.y - num::one()},
             Coordinate{x: self.x - num::one(), y: self.y + num::one()}]
-
     }
     /// Taxicab / manhattan distance: difference between X coordinates plus difference between Y coordinates
     pub fn manhattan_distance(&self, other: &Self) -> T  {
         self.x.max(other.x) - self.x.min(other.x) + self.y.max(other.y) - self.y.min(other.y)
     }
+    /// Chebyshev / chessboard distance: the larger of the differences between X and Y coordinates.
+    /// 
+    /// Two distinct points touch (including diagonally) if this distance is 1.
+    pub fn chebyshev_distance(&self, other: &Self) -> T {
+        (self.x.max(other.x) - self.x.min(other.x)).max(self.y.max(other.y) - self.y.min(other.y))
+    }
+    /// The square of the straight-line distance between two points
+    pub fn squared_euclidean_distance(&self, other: &Self) -> T {
+        let dx = self.x.max(other.x) - self.x.min(other.x);
+        let dy = self.y.max(other.y) - self.y.min(other.y);
+        dx * dx + dy * dy
+    }
+    /// Moves at most one step along each axis towards `other`, as a chess king would.
+    pub fn step_towards(&self, other: &Self) -> Self {
+        let step = |from: T, to: T| match to.cmp(&from) {
+            Ordering::Greater => from + num::one(),
+            Ordering::Less => from - num::one(),
+            Ordering::Equal => from
+        };
+        Coordinate { x: step(self.x, other.x), y: step(self.y, other.y) }
+    }
+    /// Rotates the point 90° clockwise around the supplied pivot. Y increases down the page, so
+    /// a point to the east of the pivot ends up to its south.
+    pub fn rotate_clockwise_around(&self, pivot: &Self) -> Self {
+        Coordinate { x: pivot.x + pivot.y - self.y, y: pivot.y + self.x - pivot.x }
+    }
+    /// Rotates the point 90° anticlockwise around the supplied pivot
+    pub fn rotate_anticlockwise_around(&self, pivot: &Self) -> Self {
+        Coordinate { x: pivot.x + self.y - pivot.y, y: pivot.y + pivot.x - self.x }
+    }
+    /// Reflects the point in the vertical line through `x = axis`
+    pub fn reflect_x(&self, axis: T) -> Self {
+        Coordinate { x: axis + axis - self.x, y: self.y }
+    }
+    /// Reflects the point in the horizontal line through `y = axis`
+    pub fn reflect_y(&self, axis: T) -> Self {
+        Coordinate { x: self.x, y: axis + axis - self.y }
+    }
     /// The neighbouring `Coordinate` in the supplied `Direction`
     pub fn neighbour(&self, direction: Direction) -> Self {
         match direction {
@@ -156,8 +283,125 @@ impl<T: Integer + Copy> Coordinate<T> {
 
 }
 
+impl<T: Integer + Copy + CheckedAdd + CheckedSub> Coordinate<T> {
+    /// Neighbours excluding diagonals, skipping any which would overflow or underflow `T`.
+    /// 
+    /// Safe to use on `Coordinate<usize>` at the edge of a grid, where `neighbours()` would panic in debug builds.
+    pub fn checked_neighbours(&self) -> impl Iterator<Item = Self> {
+        let one = num::one::<T>();
+        [
+            self.x.checked_sub(&one).map(|x| Coordinate{x, y: self.y}),
+            self.x.checked_add(&one).map(|x| Coordinate{x, y: self.y}),
+            self.y.checked_sub(&one).map(|y| Coordinate{x: self.x, y}),
+            self.y.checked_add(&one).map(|y| Coordinate{x: self.x, y}),
+        ].into_iter().flatten()
+    }
+    /// Neighbours including diagonals, skipping any which would overflow or underflow `T`.
+    pub fn checked_extended_neighbours(&self) -> impl Iterator<Item = Self> {
+        let one = num::one::<T>();
+        let xs = [self.x.checked_sub(&one), Some(self.x), self.x.checked_add(&one)];
+        let ys = [self.y.checked_sub(&one), Some(self.y), self.y.checked_add(&one)];
+        let centre = *self;
+        xs.into_iter().flatten()
+            .flat_map(move |x| ys.into_iter().flatten().map(move |y| Coordinate{x, y}))
+            .filter(move |c| *c != centre)
+    }
+    /// Neighbours on an alternating hex grid, in the same order as `hex_neighbours()`, skipping
+    /// any which would overflow or underflow `T`.
+    pub fn checked_hex_neighbours(&self) -> impl Iterator<Item = Self> {
+        let (one, two) = (num::one::<T>(), num::one::<T>() + num::one());
+        let at = |x: Option<T>, y: Option<T>| x.zip(y).map(|(x, y)| Coordinate{x, y});
+        [
+            at(self.x.checked_sub(&two), Some(self.y)),
+            at(self.x.checked_add(&two), Some(self.y)),
+            at(self.x.checked_add(&one), self.y.checked_sub(&one)),
+            at(self.x.checked_add(&one), self.y.checked_add(&one)),
+            at(self.x.checked_sub(&one), self.y.checked_sub(&one)),
+            at(self.x.checked_sub(&one), self.y.checked_add(&one)),
+        ].into_iter().flatten()
+    }
+    /// Neighbours excluding diagonals which lie within the supplied `Rectangle` (inclusive of its edges).
+    /// Neighbours which would overflow or underflow `T` are skipped.
+    pub fn neighbours_within(&self, bounds: &Rectangle<T>) -> impl Iterator<Item = Self> {
+        let bounds = *bounds;
+        self.checked_neighbours().filter(move |c| bounds.contains(c))
+    }
+    /// Neighbours including diagonals which lie within the supplied `Rectangle` (inclusive of its edges).
+    /// Neighbours which would overflow or underflow `T` are skipped.
+    pub fn extended_neighbours_within(&self, bounds: &Rectangle<T>) -> impl Iterator<Item = Self> {
+        let bounds = *bounds;
+        self.checked_extended_neighbours().filter(move |c| bounds.contains(c))
+    }
+}
+
+impl<T: Integer + Signed + Copy> Coordinate<T> {
+    /// Rotates the point 90° clockwise around the origin
+    pub fn rotate_clockwise(&self) -> Self {
+        Coordinate { x: -self.y, y: self.x }
+    }
+    /// Rotates the point 90° anticlockwise around the origin
+    pub fn rotate_anticlockwise(&self) -> Self {
+        Coordinate { x: self.y, y: -self.x }
+    }
+    /// All points exactly `radius` away by manhattan distance: a diamond centred on this point.
+    pub fn manhattan_ring(&self, radius: T) -> impl Iterator<Item = Self> {
+        let centre = *self;
+        let sides = if radius.is_zero() { 0 } else { 4 };
+        (0..sides).flat_map(move |side| count_up(num::zero(), radius - num::one()).map(move |i| match side {
+            0 => Coordinate { x: centre.x - radius + i, y: centre.y + i },
+            1 => Coordinate { x: centre.x + i, y: centre.y + radius - i },
+            2 => Coordinate { x: centre.x + radius - i, y: centre.y - i },
+            _ => Coordinate { x: centre.x - i, y: centre.y - radius + i },
+        })).chain((radius.is_zero()).then_some(centre))
+    }
+    /// All points no more than `radius` away by manhattan distance, in reading order
+    pub fn manhattan_disk(&self, radius: T) -> impl Iterator<Item = Self> {
+        let centre = *self;
+        count_up(-radius, radius).flat_map(move |dy| {
+            let width = radius - dy.abs();
+            count_up(-width, width).map(move |dx| Coordinate { x: centre.x + dx, y: centre.y + dy })
+        })
+    }
+    /// All points on the line between this point and `other` inclusive, using Bresenham's algorithm.
+    /// 
+    /// Horizontal, vertical and 45° lines will visit every point between the two ends.
+    pub fn line_to(&self, other: &Self) -> impl Iterator<Item = Self> {
+        let end = *other;
+        let dx = (end.x - self.x).abs();
+        let dy = -(end.y - self.y).abs();
+        let step_x = (end.x - self.x).signum();
+        let step_y = (end.y - self.y).signum();
+        let mut error = dx + dy;
+        let mut current = Some(*self);
+        std::iter::from_fn(move || {
+            let point = current?;
+            if point == end {
+                current = None;
+            } else {
+                let mut next = point;
+                let doubled = error + error;
+                if doubled >= dy {
+                    error = error + dy;
+                    next.x = next.x + step_x;
+                }
+                if doubled <= dx {
+                    error = error + dx;
+                    next.y = next.y + step_y;
+                }
+                current = Some(next);
+            }
+            Some(point)
+        })
+    }
+}
+
+/// Counts from `start` up to and including `end`
+fn count_up<T: Integer + Copy>(start: T, end: T) -> impl Iterator<Item = T> {
+    std::iter::successors(Some(start), move |i| Some(*i + num::one())).take_while(move |i| *i <= end)
+}
+
 /// Describes a rectangle aligned with the x,y,z axes by way of its top left and bottom right corners
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Rectangle<T> {
     pub top_left: Coordinate<T>,
     pub bottom_right: Coordinate<T>
@@ -176,6 +420,20 @@ impl<T: Integer + Copy> Rectangle<T> {
         let bottom_right = Coordinate{ x:  first.x.max(second.x), y: first.y.max(second.y)};
         Rectangle { top_left, bottom_right }
     }
+    /// The smallest `Rectangle` containing all of the supplied points, or `None` if there are no points
+    pub fn bounding_box<'a>(points: impl IntoIterator<Item = &'a Coordinate<T>>) -> Option<Rectangle<T>> where T: 'a {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(Rectangle { top_left: first, bottom_right: first }, |bounds, point| Rectangle {
+            top_left: Coordinate { x: bounds.top_left.x.min(point.x), y: bounds.top_left.y.min(point.y) },
+            bottom_right: Coordinate { x: bounds.bottom_right.x.max(point.x), y: bounds.bottom_right.y.max(point.y) }
+        }))
+    }
+    /// Does the rectangle contain the specified point? Points on the edges are considered to be inside.
+    pub fn contains(&self, point: &Coordinate<T>) -> bool {
+        point.x >= self.top_left.x && point.x <= self.bottom_right.x &&
+        point.y >= self.top_left.y && point.y <= self.bottom_right.y
+    }
     /// If there is an overlap between this and the other `Rectangle`, return the `Rectangle` describing the overlap
     pub fn intersection(&self, other: &Self) -> Option<Self> {
         if self.top_left.x > other.bottom_right.x || other.top_left.x > self.bottom_right.x ||
@@ -244,7 +502,12 @@ impl<T: SubAssign> SubAssign for Coordinate3d<T> {
 impl<T: Integer + Copy> Coordinate3d<T> {
     /// Returns all co-ordinates directly neighbouring the point in 3D space along X/Y/Z axes
     pub fn neighbours(&self) -> Vec<Self> {
-        vec![ Coordinate3d{x: self.x - num::one(), y: self.y, z: self.z},
+        self.neighbours_array().to_vec()
+    }
+
+    /// Returns all co-ordinates directly neighbouring the point in 3D space along X/Y/Z axes, without allocating
+    pub fn neighbours_array(&self) -> [Self; 6] {
+        [ Coordinate3d{x: self.x - num::one(), y: self.y, z: self.z},
               Coordinate3d{x: self.x + num::one(), y: self.y, z: self.z},
               Coordinate3d{x: self.x, y: self.y - num::one(), z: self.z},
               Coordinate3d{x: self.x, y: self.y + num::one(), z: self.z},
@@ -255,7 +518,12 @@ impl<T: Integer + Copy> Coordinate3d<T> {
 
     /// Returns all co-ordinates directly neighbouring the point in 3D space including diagonals
     pub fn extended_neighbours(&self) -> Vec<Self> {
-        vec![ 
+        self.extended_neighbours_array().to_vec()
+    }
+
+    /// Returns all co-ordinates directly neighbouring the point in 3D space including diagonals, without allocating
+    pub fn extended_neighbours_array(&self) -> [Self; 26] {
+        [ 
             Coordinate3d{x: self.x - num::one(), y: self.y - num::one(), z: self.z - num::one()},
             Coordinate3d{x: self.x - num::one(), y: self.y,              z: self.z - num::one()},
             Coordinate3d{x: self.x - num::one(), y: self.y + num::one(), z: self.z - num::one()},
@@ -294,6 +562,47 @@ impl<T: Integer + Copy> Coordinate3d<T> {
     }
 }
 
+impl<T: Integer + Copy + CheckedAdd + CheckedSub> Coordinate3d<T> {
+    /// Neighbours along the X/Y/Z axes, skipping any which would overflow or underflow `T`
+    pub fn checked_neighbours(&self) -> impl Iterator<Item = Self> {
+        let one = num::one::<T>();
+        [
+            self.x.checked_sub(&one).map(|x| Coordinate3d{x, y: self.y, z: self.z}),
+            self.x.checked_add(&one).map(|x| Coordinate3d{x, y: self.y, z: self.z}),
+            self.y.checked_sub(&one).map(|y| Coordinate3d{x: self.x, y, z: self.z}),
+            self.y.checked_add(&one).map(|y| Coordinate3d{x: self.x, y, z: self.z}),
+            self.z.checked_sub(&one).map(|z| Coordinate3d{x: self.x, y: self.y, z}),
+            self.z.checked_add(&one).map(|z| Coordinate3d{x: self.x, y: self.y, z}),
+        ].into_iter().flatten()
+    }
+
+    /// Neighbours including diagonals, skipping any which would overflow or underflow `T`
+    pub fn checked_extended_neighbours(&self) -> impl Iterator<Item = Self> {
+        let one = num::one::<T>();
+        let xs = [self.x.checked_sub(&one), Some(self.x), self.x.checked_add(&one)];
+        let ys = [self.y.checked_sub(&one), Some(self.y), self.y.checked_add(&one)];
+        let zs = [self.z.checked_sub(&one), Some(self.z), self.z.checked_add(&one)];
+        let centre = *self;
+        zs.into_iter().flatten()
+            .flat_map(move |z| xs.into_iter().flatten().flat_map(move |x| ys.into_iter().flatten().map(move |y| Coordinate3d{x, y, z})))
+            .filter(move |c| *c != centre)
+    }
+
+    /// Neighbours along the X/Y/Z axes which lie within the supplied `Cuboid`. Neighbours which
+    /// would overflow or underflow `T` are skipped.
+    pub fn neighbours_within(&self, bounds: &Cuboid<T>) -> impl Iterator<Item = Self> {
+        let bounds = *bounds;
+        self.checked_neighbours().filter(move |c| bounds.contains(c))
+    }
+
+    /// Neighbours including diagonals which lie within the supplied `Cuboid`. Neighbours which
+    /// would overflow or underflow `T` are skipped.
+    pub fn extended_neighbours_within(&self, bounds: &Cuboid<T>) -> impl Iterator<Item = Self> {
+        let bounds = *bounds;
+        self.checked_extended_neighbours().filter(move |c| bounds.contains(c))
+    }
+}
+
 /// Describes a cuboid aligned with the x,y,z axes by way of its top left back and bottom right front corners
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Cuboid<T> {
@@ -451,8 +760,6 @@ pub fn gcd<T: Integer + Copy >(first: T, second: T) -> T {
 /// Session token is determined by the contents of `$HOME/.aochelpers/token` if it exists;
 /// if it does not, this file will be created from the contents of the `AOCTOKEN` 
 /// environment variable, if any.
-
-
 pub fn get_daily_input(day: i32, year: i32) -> Result<String, Box<dyn Error>> {
     if let Some(mut path) = dirs::home_dir() {
         let rel_path = [".aochelpers", &year.to_string()];
@@ -461,14 +768,13 @@ pub fn get_daily_input(day: i32, year: i32) -> Result<String, Box<dyn Error>> {
             if let Ok(metadata) = fs::metadata(&path) {
                 if !metadata.is_dir(){
                     return  Err(Box::new(
-                        std::io::Error::new(ErrorKind::Other, format!("Path {} exists, but is not a directory", path.display()))
+                        std::io::Error::other(format!("Path {} exists, but is not a directory", path.display()))
                     ));
                 }
             }
         }
         path.push(day.to_string());
-        if let Ok(mut content) = std::fs::read_to_string(&path) {
-            remove_newlines(&mut content);
+        if let Ok(content) = read_input(&path) {
             Ok(content)
         } else {
             warn!("Fetching puzzle input for {} day {} from remote server", year,day);
@@ -486,8 +792,7 @@ pub fn get_daily_input(day: i32, year: i32) -> Result<String, Box<dyn Error>> {
                 .send()?;
 
             if res.status().is_success() {
-                let mut response_text = res.text()?;
-                remove_newlines(&mut response_text);
+                let response_text = normalise_input(&res.text()?);
                 let mut dir_path: PathBuf = path.clone();
                 dir_path.pop();
                 fs::create_dir_all(dir_path)?;        
@@ -497,11 +802,11 @@ pub fn get_daily_input(day: i32, year: i32) -> Result<String, Box<dyn Error>> {
             } else {
                 let mut file: File = File::create(path)?;
                 file.write_all(res.status().as_str().as_bytes())?;
-                Err(Box::new(std::io::Error::new(ErrorKind::Other, format!("Response was {}", res.status()))))
+                Err(Box::new(std::io::Error::other(format!("Response was {}", res.status()))))
             }
         }
     } else {
-        Err(Box::new(Box::new(std::io::Error::new(ErrorKind::Other,"Couldn't determine home directory"))))
+        Err(Box::new(Box::new(std::io::Error::other("Couldn't determine home directory"))))
     }
 }
 
@@ -514,21 +819,12 @@ fn get_or_create_setting_file(path: &PathBuf, env_var: &str) -> Result<String, B
         Ok(contents)
     } else {
         Err(Box::new(
-            std::io::Error::new(
-                ErrorKind::Other, format!("Can't determine setting from {} or {}", path.display(), env_var))
+            std::io::Error::other(
+                format!("Can't determine setting from {} or {}", path.display(), env_var))
         ))
     }
 }
 
-fn remove_newlines(s: &mut String) {
-    while s.ends_with('\n') {
-        s.pop();
-        if s.ends_with('\r') {
-            s.pop();
-        }
-    }
-}
-
 /// Convenience-based struct converting typical AoC node labels consisting of numbers and
 /// letters to a numeric representation, saveing all that tedious mucking about with lifetimes.
 /// 
@@ -0,0 +1,398 @@
+use std::{fmt::{self, Display}, error::Error, str::FromStr};
+use num::Integer;
+use crate::{scan, scan_fields, FromSection, ParseError, parse_error::column_of};
+
+/// The operators a `WorryExpression` can use. There's no division, as it can't be carried out
+/// on worry levels reduced to a modulus.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WorryOperator {
+    Add,
+    Subtract,
+    Multiply
+}
+
+impl Display for WorryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            WorryOperator::Add => "+",
+            WorryOperator::Subtract => "-",
+            WorryOperator::Multiply => "*"
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// An arithmetic expression over the current worry level, `old`, such as `old * old + 3`.
+///
+/// `*` binds more tightly than `+` and `-`, and brackets may be used to group terms.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WorryExpression {
+    Old,
+    Constant(i128),
+    Operation(Box<WorryExpression>, WorryOperator, Box<WorryExpression>)
+}
+
+impl WorryExpression {
+    /// Evaluates the expression for the given `old` value. If a `modulus` is supplied, the
+    /// result (and every intermediate value) is reduced to the range `0..modulus`.
+    pub fn evaluate(&self, old: i128, modulus: Option<i128>) -> i128 {
+        let reduce = |value: i128| modulus.map_or(value, |m| value.rem_euclid(m));
+        match self {
+            WorryExpression::Old => reduce(old),
+            WorryExpression::Constant(value) => reduce(*value),
+            WorryExpression::Operation(left, operator, right) => {
+                let (left, right) = (left.evaluate(old, modulus), right.evaluate(old, modulus));
+                reduce(match operator {
+                    WorryOperator::Add => left + right,
+                    WorryOperator::Subtract => left - right,
+                    WorryOperator::Multiply => left * right
+                })
+            }
+        }
+    }
+
+    fn precedence(&self) -> usize {
+        match self {
+            WorryExpression::Operation(_, WorryOperator::Add | WorryOperator::Subtract, _) => 1,
+            WorryExpression::Operation(_, _, _) => 2,
+            _ => 3
+        }
+    }
+}
+
+/// Prints the expression with only the brackets needed to preserve its meaning
+impl Display for WorryExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorryExpression::Old => write!(f, "old"),
+            WorryExpression::Constant(value) => write!(f, "{}", value),
+            WorryExpression::Operation(left, operator, right) => {
+                let precedence = self.precedence();
+                if left.precedence() < precedence {
+                    write!(f, "({})", left)?;
+                } else {
+                    write!(f, "{}", left)?;
+                }
+                write!(f, " {} ", operator)?;
+                if right.precedence() <= precedence && right.precedence() < 3 {
+                    write!(f, "({})", right)
+                } else {
+                    write!(f, "{}", right)
+                }
+            }
+        }
+    }
+}
+
+/// Recursive descent parser over an expression, tracking the byte offset reached so far
+struct ExpressionParser<'a> {
+    text: &'a str,
+    position: usize
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.position = self.text.len() - self.rest().trim_start().len();
+        self.rest().chars().next()
+    }
+
+    fn error(&self, expected: &str) -> ParseError {
+        let rest = self.rest();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        ParseError::at(self.text, &rest[..end], expected)
+    }
+
+    fn sum(&mut self) -> Result<WorryExpression, ParseError> {
+        let mut expression = self.product()?;
+        while let Some(symbol @ ('+' | '-')) = self.peek() {
+            self.position += 1;
+            let operator = if symbol == '+' { WorryOperator::Add } else { WorryOperator::Subtract };
+            expression = WorryExpression::Operation(Box::new(expression), operator, Box::new(self.product()?));
+        }
+        Ok(expression)
+    }
+
+    fn product(&mut self) -> Result<WorryExpression, ParseError> {
+        let mut expression = self.term()?;
+        while self.peek() == Some('*') {
+            self.position += 1;
+            expression = WorryExpression::Operation(Box::new(expression), WorryOperator::Multiply, Box::new(self.term()?));
+        }
+        Ok(expression)
+    }
+
+    fn term(&mut self) -> Result<WorryExpression, ParseError> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let expression = self.sum()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error("')'"));
+                }
+                self.position += 1;
+                Ok(expression)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let rest = self.rest();
+                let digits = &rest[..rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len())];
+                let value = digits.parse().map_err(|_| ParseError::at(self.text, digits, "a number which fits in an i128"))?;
+                self.position += digits.len();
+                Ok(WorryExpression::Constant(value))
+            }
+            _ if self.rest().starts_with("old") => {
+                self.position += 3;
+                Ok(WorryExpression::Old)
+            }
+            _ => Err(self.error("`old`, a number or '('"))
+        }
+    }
+}
+
+/// Example usage:
+/// ```
+/// # use aochelpers::WorryExpression;
+/// let expression = "old * (old - 2) + 3".parse::<WorryExpression>().unwrap();
+/// assert_eq!(expression.evaluate(5, None), 18);
+/// assert_eq!(expression.evaluate(5, Some(7)), 4);
+/// assert_eq!("old / 2".parse::<WorryExpression>().unwrap_err().column, 5);
+/// ```
+impl FromStr for WorryExpression {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = ExpressionParser { text: s, position: 0 };
+        let expression = parser.sum()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("one of +, - or *"));
+        }
+        Ok(expression)
+    }
+}
+
+/// A monkey from 2022 day 11, which inspects items, updates their worry levels and throws them on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monkey {
+    pub id: usize,
+    pub items: Vec<i128>,
+    pub operation: WorryExpression,
+    pub test_divisor: i128,
+    /// Where items are thrown when the worry level is divisible by `test_divisor`
+    pub if_true: usize,
+    /// Where items are thrown otherwise
+    pub if_false: usize
+}
+
+/// Parses one monkey's section of the input. Indentation and spacing are ignored; errors give
+/// the line number within the section.
+impl FromStr for Monkey {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()).collect::<Vec<_>>();
+        if lines.len() != 6 {
+            return Err(ParseError::new(0, s, "six lines describing a monkey"));
+        }
+        let line = |index: usize| lines[index].1.trim();
+        // Errors are found in the trimmed line, but reported against the original
+        let locate = |index: usize, mut error: ParseError| {
+            let (number, original) = lines[index];
+            if error.column > 0 {
+                error.column += column_of(original, original.trim_start()) - 1;
+            }
+            error.on_line(number + 1)
+        };
+
+        let (id,) = scan!("Monkey {}:", line(0) => usize).map_err(|e| locate(0, e.into()))?;
+        let items_text = line(1).strip_prefix("Starting items:")
+            .ok_or_else(|| locate(1, ParseError::at(line(1), line(1), "Starting items: <worry levels>")))?;
+        let items = items_text.split(',').map(|i| i.trim()).filter(|i| !i.is_empty())
+            .map(|i| i.parse().map_err(|_| locate(1, ParseError::at(line(1), i, "a worry level"))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let expression = scan_fields("Operation: new = {}", line(2)).map_err(|e| locate(2, e.into()))?[0];
+        let operation = expression.parse::<WorryExpression>().map_err(|mut e| {
+            e.column += column_of(line(2), expression) - 1;
+            locate(2, e)
+        })?;
+        let divisor = scan_fields("Test: divisible by {}", line(3)).map_err(|e| locate(3, e.into()))?[0];
+        let test_divisor = divisor.parse::<i128>().ok().filter(|d| *d > 0)
+            .ok_or_else(|| locate(3, ParseError::at(line(3), divisor, "a positive divisor")))?;
+        let (if_true,) = scan!("If true: throw to monkey {}", line(4) => usize).map_err(|e| locate(4, e.into()))?;
+        let (if_false,) = scan!("If false: throw to monkey {}", line(5) => usize).map_err(|e| locate(5, e.into()))?;
+        Ok(Monkey { id, items, operation, test_divisor, if_true, if_false })
+    }
+}
+
+impl FromSection for Monkey {
+    fn from_section(section: &str) -> Result<Self, anyhow::Error> {
+        Ok(section.parse()?)
+    }
+}
+
+/// How worry levels change after each inspection, once the monkey has finished with an item
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Relief {
+    /// Worry levels are divided by the given positive amount, rounding down
+    Divide(i128),
+    /// Worry levels are left alone. They are then reduced modulo the lowest common multiple of
+    /// the monkeys' divisors, which leaves every test's result unchanged.
+    Unchanged
+}
+
+/// Returned when a `MonkeySimulation` can't be set up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonkeyError {
+    /// The monkey with id `from` throws to `to`, which isn't one of the monkeys
+    UnknownTarget { from: usize, to: usize },
+    /// The monkey's test divides by zero or a negative number
+    InvalidDivisor { monkey: usize, divisor: i128 },
+    /// `Relief::Divide` was given zero or a negative amount
+    InvalidRelief(i128),
+    /// The monkey at `position` in the list has a different `id`
+    MisplacedMonkey { position: usize, id: usize }
+}
+
+impl Display for MonkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonkeyError::UnknownTarget { from, to } => write!(f, "Monkey {} throws to monkey {}, which doesn't exist", from, to),
+            MonkeyError::InvalidDivisor { monkey, divisor } => write!(f, "Monkey {} tests for divisibility by {}, which isn't positive", monkey, divisor),
+            MonkeyError::InvalidRelief(amount) => write!(f, "Relief can't divide worry levels by {}, which isn't positive", amount),
+            MonkeyError::MisplacedMonkey { position, id } => write!(f, "Monkey {} is at position {} in the list", id, position)
+        }
+    }
+}
+
+impl Error for MonkeyError {}
+
+/// A single throw of an item from one monkey to another
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Throw {
+    /// 1-based round in which the throw happened
+    pub round: usize,
+    pub from: usize,
+    pub to: usize,
+    /// The item's worry level as it was thrown
+    pub worry: i128
+}
+
+/// The results of a `MonkeySimulation`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonkeyReport {
+    /// Number of items each monkey inspected
+    pub inspections: Vec<usize>,
+    /// The items held by each monkey at the end, as (item number, worry level) pairs. Items are
+    /// numbered in the order they appear in the monkeys' starting items.
+    pub holdings: Vec<Vec<(usize, i128)>>,
+    /// Every throw made by each item, indexed by item number. Only filled in if the
+    /// simulation was asked to record trajectories.
+    pub trajectories: Vec<Vec<Throw>>
+}
+
+impl MonkeyReport {
+    /// The monkeys, most active first. Monkeys with equal counts stay in their original order.
+    pub fn most_active(&self) -> Vec<usize> {
+        let mut monkeys = (0..self.inspections.len()).collect::<Vec<_>>();
+        monkeys.sort_by_key(|m| std::cmp::Reverse(self.inspections[*m]));
+        monkeys
+    }
+
+    /// The product of the two highest inspection counts
+    pub fn monkey_business(&self) -> usize {
+        self.most_active().iter().take(2).map(|m| self.inspections[*m]).product()
+    }
+
+    pub fn total_inspections(&self) -> usize {
+        self.inspections.iter().sum()
+    }
+}
+
+/// Simulates monkeys passing items between them, as in 2022 day 11.
+///
+/// Monkeys throw to each other by position in `monkeys`, so each monkey's id must match its position.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::{Monkey, MonkeySimulation, Relief};
+/// let monkeys = [
+///     "Monkey 0:\nStarting items: 3\nOperation: new = old + old\nTest: divisible by 2\nIf true: throw to monkey 1\nIf false: throw to monkey 1",
+///     "Monkey 1:\nStarting items: 5, 4\nOperation: new = old - 1\nTest: divisible by 3\nIf true: throw to monkey 0\nIf false: throw to monkey 0",
+/// ].map(|m| m.parse::<Monkey>().unwrap());
+/// let mut simulation = MonkeySimulation::new(monkeys.to_vec(), Relief::Divide(1)).unwrap();
+/// simulation.record_trajectories = true;
+/// let report = simulation.run(1);
+/// assert_eq!(report.inspections, vec![1, 3]);
+/// assert_eq!(report.holdings, vec![vec![(1, 4), (2, 3), (0, 5)], vec![]]);
+/// assert_eq!(report.trajectories[0].iter().map(|t| (t.to, t.worry)).collect::<Vec<_>>(), vec![(1, 6), (0, 5)]);
+/// assert!(MonkeySimulation::new(monkeys.to_vec(), Relief::Divide(0)).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonkeySimulation {
+    monkeys: Vec<Monkey>,
+    relief: Relief,
+    /// Whether to record every throw in the report. Off by default, as long runs make a lot of throws.
+    pub record_trajectories: bool
+}
+
+impl MonkeySimulation {
+    pub fn new(monkeys: Vec<Monkey>, relief: Relief) -> Result<Self, MonkeyError> {
+        if let Relief::Divide(amount @ ..=0) = relief {
+            return Err(MonkeyError::InvalidRelief(amount));
+        }
+        for (position, monkey) in monkeys.iter().enumerate() {
+            if monkey.id != position {
+                return Err(MonkeyError::MisplacedMonkey { position, id: monkey.id });
+            }
+            if monkey.test_divisor <= 0 {
+                return Err(MonkeyError::InvalidDivisor { monkey: monkey.id, divisor: monkey.test_divisor });
+            }
+            if let Some(to) = [monkey.if_true, monkey.if_false].into_iter().find(|t| *t >= monkeys.len()) {
+                return Err(MonkeyError::UnknownTarget { from: monkey.id, to });
+            }
+        }
+        Ok(MonkeySimulation { monkeys, relief, record_trajectories: false })
+    }
+
+    pub fn monkeys(&self) -> &[Monkey] {
+        &self.monkeys
+    }
+
+    pub fn relief(&self) -> Relief {
+        self.relief
+    }
+
+    /// Runs the given number of rounds, in each of which every monkey in turn inspects and
+    /// throws all of the items it holds
+    pub fn run(&self, rounds: usize) -> MonkeyReport {
+        let modulus = match self.relief {
+            Relief::Divide(_) => None,
+            Relief::Unchanged => Some(self.monkeys.iter().fold(1, |lcm, m| lcm.lcm(&m.test_divisor)))
+        };
+        let mut holdings = Vec::new();
+        let mut item_count = 0;
+        for monkey in self.monkeys.iter() {
+            holdings.push(monkey.items.iter().enumerate().map(|(i, worry)| (item_count + i, *worry)).collect::<Vec<_>>());
+            item_count += monkey.items.len();
+        }
+        let mut inspections = vec![0; self.monkeys.len()];
+        let mut trajectories = vec![Vec::new(); if self.record_trajectories { item_count } else { 0 }];
+        for round in 1..=rounds {
+            for (index, monkey) in self.monkeys.iter().enumerate() {
+                for (item, worry) in std::mem::take(&mut holdings[index]) {
+                    inspections[index] += 1;
+                    let worry = match self.relief {
+                        Relief::Divide(amount) => monkey.operation.evaluate(worry, modulus).div_euclid(amount),
+                        Relief::Unchanged => monkey.operation.evaluate(worry, modulus)
+                    };
+                    let to = if worry % monkey.test_divisor == 0 { monkey.if_true } else { monkey.if_false };
+                    if self.record_trajectories {
+                        trajectories[item].push(Throw { round, from: index, to, worry });
+                    }
+                    holdings[to].push((item, worry));
+                }
+            }
+        }
+        MonkeyReport { inspections, holdings, trajectories }
+    }
+}
@@ -0,0 +1,308 @@
+use std::{fmt::{self, Display}, cmp::Ordering, str::FromStr};
+use crate::ParseError;
+
+/// A JSON-like tree of integers and strings, such as `[1,[2,"three"],[]]`.
+///
+/// `NestedList` deliberately doesn't implement `Ord`: puzzles disagree about how integers and
+/// lists compare, so pick a comparator such as `packet_cmp()` explicitly.
+///
+/// Printing with `{}` gives the compact form the tree was parsed from; `{:#}` spreads nested
+/// lists over several indented lines.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NestedList {
+    Int(i64),
+    Str(String),
+    List(Vec<NestedList>)
+}
+
+impl NestedList {
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            NestedList::Int(n) => Some(*n),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            NestedList::Str(s) => Some(s),
+            _ => None
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&Vec<NestedList>> {
+        match self {
+            NestedList::List(items) => Some(items),
+            _ => None
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<NestedList>> {
+        match self {
+            NestedList::List(items) => Some(items),
+            _ => None
+        }
+    }
+
+    pub fn is_list(&self) -> bool {
+        matches!(self, NestedList::List(_))
+    }
+
+    /// The number of lists enclosing the most deeply nested element. An integer or string has depth 0.
+    pub fn depth(&self) -> usize {
+        match self {
+            NestedList::List(items) => 1 + items.iter().map(|i| i.depth()).max().unwrap_or(0),
+            _ => 0
+        }
+    }
+
+    /// Follows a path of list indices down from the root. An empty path refers to the root itself.
+    pub fn get(&self, path: &[usize]) -> Option<&NestedList> {
+        path.iter().try_fold(self, |node, i| node.as_list()?.get(*i))
+    }
+
+    pub fn get_mut(&mut self, path: &[usize]) -> Option<&mut NestedList> {
+        path.iter().try_fold(self, |node, i| node.as_list_mut()?.get_mut(*i))
+    }
+
+    /// The path to every integer and string in the tree, in left-to-right order. Finding the
+    /// neighbours of a node (e.g. to explode a snailfish pair) is then a matter of looking either
+    /// side of it in this list.
+    pub fn leaf_paths(&self) -> Vec<Vec<usize>> {
+        fn walk(node: &NestedList, path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>) {
+            match node {
+                NestedList::List(items) => {
+                    for (i, item) in items.iter().enumerate() {
+                        path.push(i);
+                        walk(item, path, paths);
+                        path.pop();
+                    }
+                }
+                _ => paths.push(path.clone())
+            }
+        }
+        let mut paths = Vec::new();
+        walk(self, &mut Vec::new(), &mut paths);
+        paths
+    }
+
+    /// Compares two trees using the Advent of Code 2022 day 13 rules: integers compare
+    /// numerically, lists compare element by element and then by length, and when an integer
+    /// meets a list the integer is treated as a single-element list. Strings are handled the
+    /// same way as integers, and sort after them.
+    pub fn packet_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (NestedList::Int(a), NestedList::Int(b)) => a.cmp(b),
+            (NestedList::Str(a), NestedList::Str(b)) => a.cmp(b),
+            (NestedList::Int(_), NestedList::Str(_)) => Ordering::Less,
+            (NestedList::Str(_), NestedList::Int(_)) => Ordering::Greater,
+            (NestedList::List(left), NestedList::List(right)) => left.iter().zip(right.iter())
+                .map(|(l, r)| l.packet_cmp(r))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| left.len().cmp(&right.len())),
+            (NestedList::List(_), scalar) => self.packet_cmp(&NestedList::List(vec![scalar.clone()])),
+            (scalar, NestedList::List(_)) => NestedList::List(vec![scalar.clone()]).packet_cmp(other)
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        match self {
+            NestedList::List(items) if items.iter().any(|i| i.is_list()) => {
+                writeln!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{}", "  ".repeat(indent + 1))?;
+                    item.fmt_indented(f, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < items.len() { "," } else { "" })?;
+                }
+                write!(f, "{}]", "  ".repeat(indent))
+            }
+            _ => write!(f, "{}", self)
+        }
+    }
+}
+
+impl From<i64> for NestedList {
+    fn from(value: i64) -> Self {
+        NestedList::Int(value)
+    }
+}
+
+impl From<&str> for NestedList {
+    fn from(value: &str) -> Self {
+        NestedList::Str(value.to_string())
+    }
+}
+
+impl From<Vec<NestedList>> for NestedList {
+    fn from(items: Vec<NestedList>) -> Self {
+        NestedList::List(items)
+    }
+}
+
+impl Display for NestedList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_indented(f, 0);
+        }
+        match self {
+            NestedList::Int(n) => write!(f, "{}", n),
+            NestedList::Str(s) => write_string(f, s),
+            NestedList::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Writes a string in double quotes, escaping it the way `Parser::string()` expects: `\"`, `\\`,
+/// `\n`, `\r` and `\t`, and `\u{..}` for any other control character
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => write!(f, "\\{}", c)?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+            c => write!(f, "{}", c)?
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Recursive descent parser over a single line, tracking the byte offset reached so far
+struct Parser<'a> {
+    line: &'a str,
+    position: usize
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.line[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.position = self.line.len() - self.rest().trim_start().len();
+    }
+
+    fn error(&self, expected: &str) -> ParseError {
+        let rest = self.rest();
+        let end = rest.find([',', ']']).unwrap_or(rest.len()).max(rest.chars().next().map(|c| c.len_utf8()).unwrap_or(0));
+        ParseError::at(self.line, &rest[..end], expected)
+    }
+
+    fn value(&mut self) -> Result<NestedList, ParseError> {
+        self.skip_whitespace();
+        let value = match self.rest().chars().next() {
+            Some('[') => self.list()?,
+            Some('"') => self.string()?,
+            Some(c) if c == '-' || c.is_ascii_digit() => self.int()?,
+            _ => return Err(self.error("a number, string or '['"))
+        };
+        self.skip_whitespace();
+        Ok(value)
+    }
+
+    fn list(&mut self) -> Result<NestedList, ParseError> {
+        self.position += 1;
+        self.skip_whitespace();
+        let mut items = Vec::new();
+        if self.rest().starts_with(']') {
+            self.position += 1;
+            return Ok(NestedList::List(items));
+        }
+        loop {
+            items.push(self.value()?);
+            match self.rest().chars().next() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+                    return Ok(NestedList::List(items));
+                }
+                _ => return Err(self.error("',' or ']'"))
+            }
+        }
+    }
+
+    fn int(&mut self) -> Result<NestedList, ParseError> {
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let digits = rest[sign..].len() - rest[sign..].trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let text = &rest[..sign + digits];
+        let value = text.parse().map_err(|_| ParseError::at(self.line, text, "an integer which fits in an i64"))?;
+        self.position += text.len();
+        Ok(NestedList::Int(value))
+    }
+
+    fn string(&mut self) -> Result<NestedList, ParseError> {
+        let start = self.position;
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices().skip(1);
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += offset + 1;
+                    return Ok(NestedList::Str(value));
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 'r')) => value.push('\r'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, 'u')) => {
+                        let escape = &self.rest()[offset..];
+                        let digits = escape[2..].strip_prefix('{').and_then(|r| r.split_once('}')).map(|(digits, _)| digits);
+                        match digits.and_then(|d| u32::from_str_radix(d, 16).ok()).and_then(char::from_u32) {
+                            Some(decoded) => {
+                                value.push(decoded);
+                                // Skip the braces and the digits between them
+                                chars.nth(digits.unwrap().len() + 1);
+                            },
+                            None => {
+                                let end = escape.find('}').map_or(escape.len(), |e| e + 1);
+                                return Err(ParseError::at(self.line, &escape[..end], "a \\u{..} escape holding a character code in hex"));
+                            }
+                        }
+                    },
+                    Some((_, escaped)) => value.push(escaped),
+                    None => break
+                },
+                c => value.push(c)
+            }
+        }
+        Err(ParseError::at(self.line, &self.line[start..], "a closing '\"'"))
+    }
+}
+
+/// Parses a single tree. Whitespace between elements is ignored. Within strings `\n`, `\r`,
+/// `\t` and `\u{..}` are read as the characters they stand for, and `\` escapes any other
+/// character, the same way strings are escaped when printed.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::NestedList;
+/// let packet = "[1, [2, \"x\"], []]".parse::<NestedList>().unwrap();
+/// assert_eq!(packet.to_string(), "[1,[2,\"x\"],[]]");
+/// assert_eq!(packet.get(&[1, 0]), Some(&NestedList::Int(2)));
+/// let error = "[1,[2,x]]".parse::<NestedList>().unwrap_err();
+/// assert_eq!(error.column, 7);
+/// ```
+impl FromStr for NestedList {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { line, position: 0 };
+        let value = parser.value()?;
+        if parser.position < line.len() {
+            return Err(parser.error("end of line"));
+        }
+        Ok(value)
+    }
+}
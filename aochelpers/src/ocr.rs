@@ -0,0 +1,178 @@
+use std::{fmt::{self, Display}, error::Error};
+use num::Integer;
+use crate::Coordinate;
+
+/// Letters drawn 4 pixels wide and 6 high, with a single blank column between letters. `Y`
+/// is the exception, using the blank column as well.
+const SMALL_FONT: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+/// Letters drawn 6 pixels wide and 10 high, with two blank columns between letters
+const LARGE_FONT: [(char, &str); 15] = [
+    ('A', "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#"),
+    ('B', "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####."),
+    ('C', ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####."),
+    ('E', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('F', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('G', ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#"),
+    ('H', "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#"),
+    ('J', "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n#...#.\n.###.."),
+    ('K', "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#"),
+    ('L', "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('N', "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#"),
+    ('P', "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('R', "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#"),
+    ('X', "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#"),
+    ('Z', "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######"),
+];
+
+/// Returned when an image cannot be fully decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// The image is not 6 or 10 pixels high, so doesn't match either known font
+    UnknownFont { height: usize },
+    /// A character in a text image was neither the lit nor the unlit character. `line` and
+    /// `column` are 1-based.
+    UnexpectedPixel { line: usize, column: usize, found: char },
+    /// One or more glyphs did not match any known letter. `columns` holds the leftmost
+    /// column of each unrecognised glyph; `partial` is the decoded text with `?` in their place.
+    UnrecognisedGlyphs { columns: Vec<usize>, partial: String }
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::UnknownFont { height } => write!(f, "No known font is {} pixels high", height),
+            OcrError::UnexpectedPixel { line, column, found } =>
+                write!(f, "Line {}, column {}: {:?} is neither lit nor unlit", line, column, found),
+            OcrError::UnrecognisedGlyphs { columns, partial } =>
+                write!(f, "Unrecognised glyphs at columns {:?} (decoded as {})", columns, partial)
+        }
+    }
+}
+
+impl Error for OcrError {}
+
+/// Decodes letters drawn in the standard Advent of Code dot-matrix fonts.
+///
+/// Characters equal to `lit` are treated as lit pixels and characters equal to `unlit` as
+/// unlit pixels; anything else is reported as an error. Trailing whitespace and leading or
+/// trailing blank lines are ignored, so output from a CRT-style simulation can be passed in
+/// directly. The first column of the image is the first column of the first letter.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::ocr;
+/// let image = "
+/// X..X..XXX
+/// X..X...X.
+/// XXXX...X.
+/// X..X...X.
+/// X..X...X.
+/// X..X..XXX";
+/// assert_eq!(ocr(image, 'X', '.').unwrap(), "HI");
+/// assert!(ocr(image, 'X', ' ').is_err());
+/// ```
+pub fn ocr(image: &str, lit: char, unlit: char) -> Result<String, OcrError> {
+    let pixels = image.lines().enumerate().map(|(y, line)| {
+        let line = if unlit.is_whitespace() { line } else { line.trim_end() };
+        line.chars().enumerate().map(|(x, c)| match c {
+            _ if c == lit => Ok(true),
+            _ if c == unlit => Ok(false),
+            _ => Err(OcrError::UnexpectedPixel { line: y + 1, column: x + 1, found: c })
+        }).collect::<Result<Vec<_>, _>>()
+    }).collect::<Result<Vec<_>, _>>()?;
+    decode(pixels)
+}
+
+/// Decodes letters drawn by a set of lit points, such as a `HashSet<Coordinate<i32>>`.
+///
+/// `origin` is the top left corner of the image, so letters start in its column; points
+/// above or to the left of it are ignored. Blank rows at the top and bottom are skipped.
+pub fn ocr_points<'a, T>(points: impl IntoIterator<Item = &'a Coordinate<T>>, origin: &Coordinate<T>) -> Result<String, OcrError>
+        where T: Integer + Copy + TryInto<usize> + 'a {
+    let offsets = points.into_iter()
+        .filter(|p| p.x >= origin.x && p.y >= origin.y)
+        .filter_map(|p| Some(((p.x - origin.x).try_into().ok()?, (p.y - origin.y).try_into().ok()?)))
+        .collect::<Vec<(usize, usize)>>();
+    let width = offsets.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = offsets.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+    let mut pixels = vec![vec![false; width]; height];
+    for (x, y) in offsets {
+        pixels[y][x] = true;
+    }
+    decode(pixels)
+}
+
+/// Decodes letters drawn on a dense grid of pixels, such as a `HashMap<Coordinate<i32>, bool>`.
+/// The image starts at the top left corner of the grid, lit or not.
+pub fn ocr_grid<'a, T>(grid: impl IntoIterator<Item = (&'a Coordinate<T>, &'a bool)>) -> Result<String, OcrError>
+        where T: Integer + Copy + TryInto<usize> + 'a {
+    let cells = grid.into_iter().collect::<Vec<_>>();
+    let (Some(min_x), Some(min_y)) = (cells.iter().map(|(c, _)| c.x).min(), cells.iter().map(|(c, _)| c.y).min()) else {
+        return Ok(String::new());
+    };
+    ocr_points(cells.iter().filter(|(_, lit)| **lit).map(|(c, _)| *c), &Coordinate { x: min_x, y: min_y })
+}
+
+fn decode(mut pixels: Vec<Vec<bool>>) -> Result<String, OcrError> {
+    while pixels.first().is_some_and(|row| !row.contains(&true)) {
+        pixels.remove(0);
+    }
+    while pixels.last().is_some_and(|row| !row.contains(&true)) {
+        pixels.pop();
+    }
+    if pixels.is_empty() {
+        return Ok(String::new());
+    }
+    // Each letter is compared along with the blank columns after it
+    let (font, stride) = match pixels.len() {
+        6 => (&SMALL_FONT[..], 5),
+        10 => (&LARGE_FONT[..], 8),
+        height => return Err(OcrError::UnknownFont { height })
+    };
+    let width = pixels.iter().map(|row| row.len()).max().unwrap_or(0);
+    let pixel = |x: usize, y: usize| pixels[y].get(x).copied().unwrap_or(false);
+    let matches = |pattern: &str, start: usize| pattern.lines().enumerate().all(|(y, row)| {
+        let row = row.chars().map(|c| c == '#').collect::<Vec<_>>();
+        (0..stride).all(|x| row.get(x).copied().unwrap_or(false) == pixel(start + x, y))
+    });
+
+    let mut decoded = String::new();
+    let mut unrecognised = Vec::new();
+    for start in (0..width).step_by(stride) {
+        if !(0..pixels.len()).any(|y| (start..start + stride).any(|x| pixel(x, y))) {
+            continue;
+        }
+        match font.iter().find(|(_, pattern)| matches(pattern, start)) {
+            Some((letter, _)) => decoded.push(*letter),
+            None => {
+                decoded.push('?');
+                unrecognised.push(start);
+            }
+        }
+    }
+    if unrecognised.is_empty() {
+        Ok(decoded)
+    } else {
+        Err(OcrError::UnrecognisedGlyphs { columns: unrecognised, partial: decoded })
+    }
+}
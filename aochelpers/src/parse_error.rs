@@ -0,0 +1,142 @@
+use std::{fmt::{self, Display}, error::Error, io, num::{ParseIntError, ParseFloatError}, char::ParseCharError, str::ParseBoolError};
+use crate::ScanError;
+
+/// A parse failure, located within the puzzle input.
+///
+/// Per-line parsers usually only know the column at which they failed; `parse_lines()` fills
+/// in the line number, and treats errors without a column as applying to the whole line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number within the input, or 0 if not known
+    pub line: usize,
+    /// 1-based character position within the line, or 0 if not known
+    pub column: usize,
+    /// The text which couldn't be parsed
+    pub snippet: String,
+    /// What was expected in place of `snippet`
+    pub expected: String
+}
+
+impl ParseError {
+    pub fn new(column: usize, snippet: impl Into<String>, expected: impl Into<String>) -> Self {
+        ParseError { line: 0, column, snippet: snippet.into(), expected: expected.into() }
+    }
+
+    /// Builds an error for `fragment`, which should be a slice of `line`, working out its column.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use aochelpers::ParseError;
+    /// let line = "x=12, y=abc";
+    /// let error = ParseError::at(line, &line[8..], "a number");
+    /// assert_eq!(error.column, 9);
+    /// assert_eq!(error.to_string(), "column 9: expected a number, found \"abc\"");
+    /// ```
+    pub fn at(line: &str, fragment: &str, expected: impl Into<String>) -> Self {
+        ParseError::new(column_of(line, fragment), fragment, expected)
+    }
+
+    /// Records the line of the input on which the error occurred
+    pub fn on_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+}
+
+/// The 1-based character column at which `fragment` starts within `line`, or 1 if `fragment`
+/// isn't a slice of `line`.
+pub(crate) fn column_of(line: &str, fragment: &str) -> usize {
+    let offset = (fragment.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+    if offset <= line.len() && line.is_char_boundary(offset) {
+        line[..offset].chars().count() + 1
+    } else {
+        1
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = [("line", self.line), ("column", self.column)].iter()
+            .filter(|(_, value)| *value > 0)
+            .map(|(name, value)| format!("{} {}", name, value))
+            .collect::<Vec<_>>();
+        if !location.is_empty() {
+            write!(f, "{}: ", location.join(", "))?;
+        }
+        write!(f, "expected {}, found {:?}", self.expected, self.snippet)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Allows `?` to pass parse errors up through the `io::Result` returned by each day's `main()`
+impl From<ParseError> for io::Error {
+    fn from(error: ParseError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+impl From<ScanError> for ParseError {
+    fn from(error: ScanError) -> Self {
+        match error {
+            ScanError::Mismatch { column, expected, found } => ParseError::new(column, found, format!("{:?}", expected)),
+            ScanError::InvalidField { index, column, value, reason } =>
+                ParseError::new(column, value, format!("field {} to be valid ({})", index, reason)),
+            ScanError::FieldCount { template, requested } =>
+                ParseError::new(0, "", format!("{} fields (template has {})", requested, template))
+        }
+    }
+}
+
+macro_rules! parse_error_from_std {
+    ($($error:ty => $expected:expr),*) => {
+        $(impl From<$error> for ParseError {
+            fn from(error: $error) -> Self {
+                ParseError::new(0, "", format!("{} ({})", $expected, error))
+            }
+        })*
+    };
+}
+
+parse_error_from_std!(ParseIntError => "an integer", ParseFloatError => "a number", ParseCharError => "a single character", ParseBoolError => "true or false");
+
+fn locate(error: impl Into<ParseError>, index: usize, line: &str) -> ParseError {
+    let mut error = error.into().on_line(index + 1);
+    if error.column == 0 {
+        let trimmed = line.trim();
+        error.column = column_of(line, trimmed);
+        error.snippet = trimmed.to_string();
+    }
+    error
+}
+
+/// Parses each line of the input with the supplied parser, stopping at the first line which
+/// fails. The error records which line that was.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::parse_lines;
+/// assert_eq!(parse_lines("1\n2\n3", |l| l.parse::<i32>()), Ok(vec![1, 2, 3]));
+/// let error = parse_lines("1\n2\nthree", |l| l.parse::<i32>()).unwrap_err();
+/// assert_eq!(error.line, 3);
+/// assert_eq!(error.snippet, "three");
+/// ```
+pub fn parse_lines<T, E: Into<ParseError>>(input: &str, parser: impl Fn(&str) -> Result<T, E>) -> Result<Vec<T>, ParseError> {
+    input.lines().enumerate()
+        .map(|(i, line)| parser(line).map_err(|e| locate(e, i, line)))
+        .collect()
+}
+
+/// Parses each line of the input with the supplied parser, reporting every line which fails
+/// rather than just the first.
+pub fn parse_lines_all<T, E: Into<ParseError>>(input: &str, parser: impl Fn(&str) -> Result<T, E>) -> Result<Vec<T>, Vec<ParseError>> {
+    let mut parsed = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        match parser(line) {
+            Ok(value) => parsed.push(value),
+            Err(e) => errors.push(locate(e, i, line))
+        }
+    }
+    if errors.is_empty() { Ok(parsed) } else { Err(errors) }
+}
@@ -0,0 +1,113 @@
+use num::{Integer, Signed};
+use crate::{Coordinate, Direction};
+
+/// A closed polygon on an integer lattice, described by its vertices in order.
+///
+/// The last vertex is assumed to join back up to the first. Vertices may be listed
+/// clockwise or anticlockwise.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Polygon<T> {
+    pub vertices: Vec<Coordinate<T>>
+}
+
+impl<T: Integer + Signed + Copy> Polygon<T> {
+    /// Builds a `Polygon` from an ordered loop of vertices. If the loop is explicitly closed
+    /// (the last vertex repeats the first) the repeated vertex is dropped.
+    pub fn new(mut vertices: Vec<Coordinate<T>>) -> Self {
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    /// Builds a `Polygon` by walking from `start`, taking each `(Direction, distance)` step in turn
+    /// and recording a vertex at the end of each step.
+    ///
+    /// Example usage, for a 3x3 square of points:
+    /// ```
+    /// # use aochelpers::{Coordinate, Direction, Polygon};
+    /// let steps = [(Direction::East, 2), (Direction::South, 2), (Direction::West, 2), (Direction::North, 2)];
+    /// let square = Polygon::from_steps(Coordinate{x: 0, y: 0}, steps);
+    /// assert_eq!(square.enclosed_points(), 9);
+    /// ```
+    pub fn from_steps(start: Coordinate<T>, steps: impl IntoIterator<Item = (Direction, T)>) -> Self {
+        let mut vertices = vec![start];
+        let mut position = start;
+        for (direction, distance) in steps {
+            let delta = position.neighbour(direction) - position;
+            position = position + Coordinate { x: delta.x * distance, y: delta.y * distance };
+            vertices.push(position);
+        }
+        Polygon::new(vertices)
+    }
+
+    /// Iterates over each edge of the polygon as a pair of vertices, including the closing edge
+    fn edges(&self) -> impl Iterator<Item = (Coordinate<T>, Coordinate<T>)> + '_ {
+        self.vertices.iter().zip(self.vertices.iter().cycle().skip(1)).map(|(a, b)| (*a, *b))
+    }
+
+    /// Twice the enclosed area, as calculated by the shoelace formula. Always an integer for
+    /// polygons with lattice vertices.
+    pub fn doubled_area(&self) -> T {
+        self.edges().fold(num::zero::<T>(), |acc, (a, b)| acc + a.x * b.y - b.x * a.y).abs()
+    }
+
+    /// The enclosed area of the polygon, treating vertices as points rather than squares.
+    /// Rounds down if the area is not a whole number.
+    pub fn area(&self) -> T {
+        self.doubled_area() / (num::one::<T>() + num::one())
+    }
+
+    /// The number of lattice points which lie on the edges of the polygon
+    pub fn boundary_points(&self) -> T {
+        self.edges().fold(num::zero(), |acc, (a, b)| acc + (b.x - a.x).abs().gcd(&(b.y - a.y).abs()))
+    }
+
+    /// The number of lattice points strictly inside the polygon, by Pick's theorem:
+    /// `A = i + b/2 - 1`. Polygons with no area (fewer than 3 vertices, or all in a line)
+    /// have no interior points.
+    pub fn interior_points(&self) -> T {
+        let two = num::one::<T>() + num::one();
+        let doubled_area = self.doubled_area();
+        if doubled_area.is_zero() {
+            return num::zero();
+        }
+        (doubled_area - self.boundary_points() + two) / two
+    }
+
+    /// The number of lattice points inside the polygon or on its boundary.
+    ///
+    /// If each point represents a square (e.g. a trench dug around a lagoon), this is the
+    /// total number of squares enclosed by the loop.
+    pub fn enclosed_points(&self) -> T {
+        self.interior_points() + self.boundary_points()
+    }
+
+    /// Does the supplied point lie on one of the polygon's edges?
+    pub fn on_boundary(&self, point: &Coordinate<T>) -> bool {
+        self.edges().any(|(a, b)| {
+            (b.x - a.x) * (point.y - a.y) == (b.y - a.y) * (point.x - a.x) &&
+            point.x >= a.x.min(b.x) && point.x <= a.x.max(b.x) &&
+            point.y >= a.y.min(b.y) && point.y <= a.y.max(b.y)
+        })
+    }
+
+    /// Does the polygon contain the supplied point? Points on the boundary are considered to be inside.
+    pub fn contains(&self, point: &Coordinate<T>) -> bool {
+        if self.on_boundary(point) {
+            return true;
+        }
+        // Cast a ray eastwards from the point and count the edges it crosses
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a.y > point.y) != (b.y > point.y) {
+                let lhs = (point.x - a.x) * (b.y - a.y);
+                let rhs = (point.y - a.y) * (b.x - a.x);
+                if (b.y > a.y && lhs < rhs) || (b.y < a.y && lhs > rhs) {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
@@ -0,0 +1,260 @@
+use std::{fmt::{self, Display}, error::Error, str::FromStr};
+use crate::{scan, scan_fields, ParseError, parse_error::column_of};
+
+/// How to build a producer (a robot) which gathers one unit of `produces` every minute
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Recipe {
+    pub produces: String,
+    /// The resources used up by building the producer, and how many of each
+    pub costs: Vec<(String, u32)>
+}
+
+/// Parses recipes such as `Each obsidian robot costs 3 ore and 14 clay`, with or without a
+/// full stop at the end
+impl FromStr for Recipe {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim_end();
+        let sentence = trimmed.strip_suffix('.').unwrap_or(trimmed);
+        let fields = scan_fields("Each {} robot costs {}", sentence)?;
+        let (produces, costs) = (fields[0].to_string(), fields[1]);
+        let costs = split_on_and(costs).into_iter().map(|cost| {
+            scan!("{} {}", cost => u32, String).map_err(|e| {
+                let mut error = ParseError::from(e);
+                if error.column > 0 {
+                    error.column += column_of(sentence, cost) - 1;
+                }
+                error
+            })
+        }).collect::<Result<Vec<_>, _>>()?;
+        Ok(Recipe { produces, costs: costs.into_iter().map(|(amount, resource)| (resource, amount)).collect() })
+    }
+}
+
+/// Splits `text` around each occurrence of the word `and`, whatever whitespace surrounds it. The
+/// parts are trimmed slices of `text`, so errors within them can still be located.
+fn split_on_and(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (index, word) in text.match_indices("and") {
+        let end = index + word.len();
+        if text[..index].ends_with(char::is_whitespace) && text[end..].starts_with(char::is_whitespace) {
+            parts.push(text[start..index].trim());
+            start = end;
+        }
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+impl Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let costs = self.costs.iter().map(|(resource, amount)| format!("{} {}", amount, resource)).collect::<Vec<_>>();
+        write!(f, "Each {} robot costs {}.", self.produces, costs.join(" and "))
+    }
+}
+
+/// Parses recipes written as sentences, such as
+/// `Each ore robot costs 4 ore. Each clay robot costs 2 ore.`, which may be split across lines
+impl FromStr for Factory {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let recipes = s.split('.').map(str::trim).filter(|r| !r.is_empty()).map(|recipe| {
+            recipe.parse::<Recipe>().map_err(|mut e| {
+                if e.column > 0 {
+                    e.column += column_of(s, recipe) - 1;
+                }
+                e
+            })
+        }).collect::<Result<Vec<_>, _>>()?;
+        Ok(Factory::new(recipes))
+    }
+}
+
+/// Returned when a `Factory` is asked about a resource none of its recipes mention
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProductionError {
+    UnknownResource(String)
+}
+
+impl Display for ProductionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProductionError::UnknownResource(name) => write!(f, "No recipe uses or produces {}", name)
+        }
+    }
+}
+
+impl Error for ProductionError {}
+
+/// A producer built as part of a `ProductionPlan`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Build {
+    /// The minute (counting from 1) spent building the producer. It starts gathering in the
+    /// following minute.
+    pub minute: u32,
+    /// The index of the recipe used, within `Factory::recipes()`
+    pub recipe: usize
+}
+
+/// The best result found by `Factory::optimise()`, and the builds which achieve it
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ProductionPlan {
+    pub produced: u32,
+    pub builds: Vec<Build>
+}
+
+/// A factory which can build one producer a minute from a set of recipes, as with the robots
+/// of 2022 day 19. Each producer gathers one unit of its resource a minute, starting the minute
+/// after it's built.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::{Factory, Recipe};
+/// let recipes = ["Each ore robot costs 4 ore.", "Each clay robot costs 2 ore.",
+///                "Each obsidian robot costs 3 ore and 14 clay.", "Each geode robot costs 2 ore and 7 obsidian."];
+/// let factory = Factory::new(recipes.iter().map(|r| r.parse::<Recipe>().unwrap()));
+/// let plan = factory.optimise(&[("ore", 1)], 24, "geode").unwrap();
+/// assert_eq!(plan.produced, 9);
+/// // Ore robots are too expensive to be worth it, so the plan starts with a clay robot
+/// assert_eq!(factory.recipes()[plan.builds[0].recipe].produces, "clay");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factory {
+    recipes: Vec<Recipe>,
+    resources: Vec<String>,
+    /// For each recipe, the index of the resource it produces and the cost of each resource
+    indexed: Vec<(usize, Vec<u32>)>
+}
+
+impl Factory {
+    pub fn new(recipes: impl IntoIterator<Item = Recipe>) -> Self {
+        let recipes = recipes.into_iter().collect::<Vec<_>>();
+        let mut resources: Vec<String> = Vec::new();
+        for name in recipes.iter().flat_map(|r| std::iter::once(&r.produces).chain(r.costs.iter().map(|(name, _)| name))) {
+            if !resources.contains(name) {
+                resources.push(name.clone());
+            }
+        }
+        let index_of = |name: &String| resources.iter().position(|r| r == name).unwrap();
+        let indexed = recipes.iter().map(|recipe| {
+            let mut costs = vec![0; resources.len()];
+            for (name, amount) in recipe.costs.iter() {
+                costs[index_of(name)] += amount;
+            }
+            (index_of(&recipe.produces), costs)
+        }).collect();
+        Factory { recipes, resources, indexed }
+    }
+
+    pub fn recipes(&self) -> &[Recipe] {
+        &self.recipes
+    }
+
+    /// Every resource mentioned by the recipes, in order of first mention
+    pub fn resources(&self) -> &[String] {
+        &self.resources
+    }
+
+    fn index_of(&self, name: &str) -> Result<usize, ProductionError> {
+        self.resources.iter().position(|r| r == name).ok_or_else(|| ProductionError::UnknownResource(name.to_string()))
+    }
+
+    /// Finds the most of `target` which can be gathered in `time` minutes, starting with no
+    /// resources and the given numbers of producers, along with the builds needed.
+    ///
+    /// This is a depth first search which jumps straight to the minute in which each possible
+    /// next producer can be afforded, rather than stepping through every minute. A branch is
+    /// abandoned if even building every kind of producer as soon as it could be afforded from
+    /// a separate stock of resources couldn't beat the best plan found so far. The factory also
+    /// never builds more producers of a resource other than `target` than could be spent in a
+    /// minute.
+    pub fn optimise(&self, producers: &[(&str, u32)], time: u32, target: &str) -> Result<ProductionPlan, ProductionError> {
+        let target = self.index_of(target)?;
+        let mut starting_producers = vec![0; self.resources.len()];
+        for (name, count) in producers {
+            starting_producers[self.index_of(name)?] += count;
+        }
+        let most_needed = (0..self.resources.len()).map(|resource| match resource == target {
+            true => u32::MAX,
+            false => self.indexed.iter().map(|(_, costs)| costs[resource]).max().unwrap_or(0)
+        }).collect();
+        // Recipes for the target first, then the rest latest first, as later recipes tend to be
+        // closer to the target
+        let mut order = (0..self.recipes.len()).rev().collect::<Vec<_>>();
+        order.sort_by_key(|r| self.indexed[*r].0 != target);
+        let mut search = Search { factory: self, target, time, most_needed, order, best: ProductionPlan::default(), builds: Vec::new() };
+        search.explore(0, &vec![0; self.resources.len()], &starting_producers);
+        Ok(search.best)
+    }
+}
+
+struct Search<'a> {
+    factory: &'a Factory,
+    target: usize,
+    time: u32,
+    most_needed: Vec<u32>,
+    order: Vec<usize>,
+    best: ProductionPlan,
+    builds: Vec<Build>
+}
+
+impl Search<'_> {
+    fn explore(&mut self, minute: u32, stock: &[u32], producers: &[u32]) {
+        let remaining = self.time - minute;
+        let without_building = stock[self.target] + producers[self.target] * remaining;
+        if without_building > self.best.produced {
+            self.best = ProductionPlan { produced: without_building, builds: self.builds.clone() };
+        }
+        if self.upper_bound(remaining, stock, producers) <= self.best.produced {
+            return;
+        }
+        for recipe in self.order.clone() {
+            let (produces, costs) = &self.factory.indexed[recipe];
+            if producers[*produces] >= self.most_needed[*produces] {
+                continue;
+            }
+            // Minutes spent gathering before the producer can be afforded
+            let wait = costs.iter().zip(stock).zip(producers).try_fold(0, |wait, ((cost, have), rate)| match cost.saturating_sub(*have) {
+                0 => Some(wait),
+                _ if *rate == 0 => None,
+                short => Some(wait.max(short.div_ceil(*rate)))
+            });
+            let Some(wait) = wait.filter(|w| minute + w + 1 < self.time) else {
+                continue;
+            };
+            let next_stock = (0..stock.len()).map(|r| stock[r] + producers[r] * (wait + 1) - costs[r]).collect::<Vec<_>>();
+            let mut next_producers = producers.to_vec();
+            next_producers[*produces] += 1;
+            self.builds.push(Build { minute: minute + wait + 1, recipe });
+            self.explore(minute + wait + 1, &next_stock, &next_producers);
+            self.builds.pop();
+        }
+    }
+
+    /// The most of the target which could be gathered if each recipe had its own separate stock
+    /// of resources to pay from, so that any number of different producers could be built each
+    /// minute
+    fn upper_bound(&self, remaining: u32, stock: &[u32], producers: &[u32]) -> u32 {
+        let mut stocks = vec![stock.to_vec(); self.factory.indexed.len()];
+        let mut producers = producers.to_vec();
+        let mut gathered = stock[self.target];
+        for _ in 0..remaining {
+            let mut built = Vec::new();
+            for ((produces, costs), stock) in self.factory.indexed.iter().zip(stocks.iter_mut()) {
+                if costs.iter().zip(stock.iter()).all(|(cost, have)| have >= cost) {
+                    stock.iter_mut().zip(costs).for_each(|(have, cost)| *have -= cost);
+                    built.push(*produces);
+                }
+            }
+            for stock in stocks.iter_mut() {
+                stock.iter_mut().zip(producers.iter()).for_each(|(have, rate)| *have += rate);
+            }
+            gathered += producers[self.target];
+            for resource in built {
+                producers[resource] += 1;
+            }
+        }
+        gathered
+    }
+}
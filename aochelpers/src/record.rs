@@ -0,0 +1,118 @@
+use std::{fs::File, io::{self, BufWriter, Write}, path::Path, error::Error, time::Duration, collections::HashMap};
+use num::{Integer, ToPrimitive};
+use crate::{Coordinate, Frame, Rectangle, BACKGROUND};
+
+/// Collects a `Frame` from each step of a simulation, for playback in a terminal or export
+/// as an animated GIF.
+///
+/// Consecutive identical frames are only stored once, so it is safe to record every step of a
+/// simulation which spends long periods unchanged.
+#[derive(Debug, Clone)]
+pub struct Recorder<T> {
+    pub frames: Vec<Frame<T>>,
+    /// Time each frame is displayed for
+    pub delay: Duration
+}
+
+impl<T: Integer + Copy + ToPrimitive> Recorder<T> {
+    pub fn new(delay: Duration) -> Self {
+        Recorder { frames: Vec::new(), delay }
+    }
+
+    /// Adds a frame to the recording. Returns `false` if the frame was discarded because it is
+    /// identical to the previous one.
+    pub fn record(&mut self, frame: Frame<T>) -> bool {
+        if self.frames.last() == Some(&frame) {
+            false
+        } else {
+            self.frames.push(frame);
+            true
+        }
+    }
+
+    /// Records a sparse set of points, as rendered by `Frame::from_points`
+    pub fn record_points<'a>(&mut self, points: impl IntoIterator<Item = &'a Coordinate<T>>, glyph: char) -> bool where T: 'a {
+        self.record(Frame::from_points(points, glyph))
+    }
+
+    /// Records a map of cells, as rendered by `Frame::from_map`
+    pub fn record_map<'a, V: 'a>(&mut self, grid: impl IntoIterator<Item = (&'a Coordinate<T>, &'a V)>, glyph: impl Fn(&V) -> char) -> bool where T: 'a {
+        self.record(Frame::from_map(grid, glyph))
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The smallest region containing every recorded frame
+    pub fn bounds(&self) -> Option<Rectangle<T>> {
+        let corners = self.frames.iter()
+            .filter(|f| f.height() > 0)
+            .flat_map(|f| [f.bounds.top_left, f.bounds.bottom_right])
+            .collect::<Vec<_>>();
+        Rectangle::bounding_box(&corners)
+    }
+
+    /// Each recorded frame redrawn to cover `bounds()`, so that all frames share the same size
+    fn aligned_frames(&self) -> Vec<Frame<T>> {
+        let Some(bounds) = self.bounds() else {
+            return Vec::new();
+        };
+        self.frames.iter()
+            .map(|frame| Frame::new(bounds, |c| frame.get(c).unwrap_or(BACKGROUND)))
+            .collect()
+    }
+
+    /// Plays the recording to the supplied output, clearing the terminal between frames
+    /// and pausing for `delay` after each.
+    pub fn play(&self, output: &mut impl Write) -> io::Result<()> {
+        for frame in self.aligned_frames() {
+            writeln!(output, "\x1b[2J\x1b[H{}", frame)?;
+            output.flush()?;
+            std::thread::sleep(self.delay);
+        }
+        Ok(())
+    }
+
+    /// Writes the recording as a looping animated GIF, drawing each cell as a `scale` x `scale`
+    /// square. The palette may produce at most 256 distinct colours.
+    pub fn write_gif(&self, path: impl AsRef<Path>, scale: usize, palette: impl Fn(char) -> [u8; 3]) -> Result<(), Box<dyn Error>> {
+        let frames = self.aligned_frames();
+        let images = frames.iter().map(|f| f.to_rgb(scale, &palette)).collect::<Vec<_>>();
+        let (width, height) = images.first().map(|(w, h, _)| (*w, *h)).unwrap_or((0, 0));
+
+        let mut colours: HashMap<[u8; 3], u8> = HashMap::new();
+        let mut indexed_images = Vec::new();
+        for (_, _, pixels) in images.iter() {
+            let mut indices = Vec::with_capacity(pixels.len() / 3);
+            for pixel in pixels.chunks(3) {
+                let next_index = colours.len();
+                let index = *colours.entry([pixel[0], pixel[1], pixel[2]]).or_insert(next_index as u8);
+                if colours.len() > 256 {
+                    return Err("Palette produces more than 256 colours".into());
+                }
+                indices.push(index);
+            }
+            indexed_images.push(indices);
+        }
+        let mut global_palette = vec![0_u8; colours.len().max(2).next_power_of_two() * 3];
+        for (colour, index) in colours.iter() {
+            global_palette[*index as usize * 3..*index as usize * 3 + 3].copy_from_slice(colour);
+        }
+
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = gif::Encoder::new(file, u16::try_from(width)?, u16::try_from(height)?, &global_palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        let delay = u16::try_from(self.delay.as_millis() / 10)?;
+        for indices in indexed_images {
+            let mut gif_frame = gif::Frame::from_indexed_pixels(width as u16, height as u16, indices, None);
+            gif_frame.delay = delay;
+            encoder.write_frame(&gif_frame)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::{fmt::{self, Display}, fs::File, io::{BufWriter, Write}, path::Path, error::Error};
+use num::{Integer, ToPrimitive};
+use crate::{Coordinate, Rectangle};
+
+/// The glyph used for cells with nothing in them
+pub const BACKGROUND: char = '.';
+
+/// A rectangular snapshot of a grid, rendered as one glyph per cell.
+///
+/// Frames can be printed as text (via `Display`, or `labelled()` for axis labels), or
+/// written out as PPM or PNG images for grids too large to read in a terminal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame<T> {
+    /// The region of the grid covered by this frame
+    pub bounds: Rectangle<T>,
+    /// Glyphs in reading order: `cells[y][x]`, relative to the top left of `bounds`
+    pub cells: Vec<Vec<char>>
+}
+
+impl<T: Integer + Copy + ToPrimitive> Frame<T> {
+    /// Renders every cell in `bounds` (inclusive of its edges) using the supplied glyph function
+    pub fn new(bounds: Rectangle<T>, glyph: impl Fn(&Coordinate<T>) -> char) -> Self {
+        let cells = crate::count_up(bounds.top_left.y, bounds.bottom_right.y)
+            .map(|y| crate::count_up(bounds.top_left.x, bounds.bottom_right.x)
+                .map(|x| glyph(&Coordinate { x, y }))
+                .collect())
+            .collect();
+        Frame { bounds, cells }
+    }
+
+    /// Renders a sparse set of points, such as a `HashSet<Coordinate<i32>>`, as `glyph` on a
+    /// background of `.`. The frame covers the bounding box of the points; an empty set
+    /// renders as an empty frame.
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Coordinate<T>>, glyph: char) -> Self where T: 'a {
+        let points = points.into_iter().collect::<Vec<_>>();
+        let Some(bounds) = Rectangle::bounding_box(points.iter().copied()) else {
+            let origin = Coordinate { x: num::zero(), y: num::zero() };
+            return Frame { bounds: Rectangle::new(origin, origin), cells: Vec::new() };
+        };
+        let mut frame = Frame::new(bounds, |_| BACKGROUND);
+        frame.overlay(points, glyph);
+        frame
+    }
+
+    /// Renders a map of cells, such as a `HashMap<Coordinate<i32>, Tile>`, converting each value
+    /// to a glyph with the supplied function. Cells missing from the map are rendered as `.`
+    pub fn from_map<'a, V: 'a>(grid: impl IntoIterator<Item = (&'a Coordinate<T>, &'a V)>, glyph: impl Fn(&V) -> char) -> Self where T: 'a {
+        let cells = grid.into_iter().collect::<Vec<_>>();
+        let mut frame = Frame::from_points(cells.iter().map(|(c, _)| *c), BACKGROUND);
+        for (coordinate, value) in cells {
+            frame.set(coordinate, glyph(value));
+        }
+        frame
+    }
+
+    /// Converts a coordinate to a `(row, column)` position within `cells`
+    fn position(&self, point: &Coordinate<T>) -> Option<(usize, usize)> {
+        if !self.bounds.contains(point) {
+            return None;
+        }
+        let column = (point.x - self.bounds.top_left.x).to_usize()?;
+        let row = (point.y - self.bounds.top_left.y).to_usize()?;
+        (row < self.cells.len() && column < self.cells[row].len()).then_some((row, column))
+    }
+
+    /// The glyph at the supplied point, if it lies within the frame
+    pub fn get(&self, point: &Coordinate<T>) -> Option<char> {
+        self.position(point).map(|(row, column)| self.cells[row][column])
+    }
+
+    /// Sets the glyph at the supplied point. Returns `false` if the point lies outside the frame.
+    pub fn set(&mut self, point: &Coordinate<T>, glyph: char) -> bool {
+        if let Some((row, column)) = self.position(point) {
+            self.cells[row][column] = glyph;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Draws `glyph` over each of the supplied points, such as a path or the current position.
+    /// Points outside the frame are ignored.
+    pub fn overlay<'a>(&mut self, points: impl IntoIterator<Item = &'a Coordinate<T>>, glyph: char) where T: 'a {
+        for point in points {
+            self.set(point, glyph);
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.first().map(|row| row.len()).unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Renders the frame as text, with X values written vertically above each column
+    /// and Y values to the left of each row.
+    pub fn labelled(&self) -> String where T: Display {
+        let x_labels = crate::count_up(self.bounds.top_left.x, self.bounds.bottom_right.x)
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        let y_labels = crate::count_up(self.bounds.top_left.y, self.bounds.bottom_right.y)
+            .map(|y| y.to_string())
+            .collect::<Vec<_>>();
+        let label_height = x_labels.iter().map(|l| l.len()).max().unwrap_or(0);
+        let label_width = y_labels.iter().map(|l| l.len()).max().unwrap_or(0);
+
+        let mut output = Vec::new();
+        for line in 0..label_height {
+            let header = x_labels.iter()
+                .map(|l| l.chars().rev().chain(std::iter::repeat(' ')).nth(label_height - line - 1).unwrap())
+                .collect::<String>();
+            output.push(format!("{} {}", " ".repeat(label_width), header).trim_end().to_string());
+        }
+        for (label, row) in y_labels.iter().zip(self.cells.iter()) {
+            output.push(format!("{:>width$} {}", label, row.iter().collect::<String>(), width = label_width));
+        }
+        output.join("\n")
+    }
+
+    /// Converts the frame to 8-bit RGB pixel data, drawing each cell as a `scale` x `scale` square.
+    /// Returns the image width, height and pixel data.
+    pub fn to_rgb(&self, scale: usize, palette: impl Fn(char) -> [u8; 3]) -> (usize, usize, Vec<u8>) {
+        let width = self.width() * scale;
+        let height = self.height() * scale;
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for row in self.cells.iter() {
+            let line = row.iter()
+                .flat_map(|c| std::iter::repeat_n(palette(*c), scale))
+                .flatten()
+                .collect::<Vec<_>>();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        (width, height, pixels)
+    }
+
+    /// Writes the frame as a binary PPM image, one pixel per cell
+    pub fn write_ppm(&self, path: impl AsRef<Path>, palette: impl Fn(char) -> [u8; 3]) -> std::io::Result<()> {
+        let (width, height, pixels) = self.to_rgb(1, palette);
+        let mut file = BufWriter::new(File::create(path)?);
+        write!(file, "P6\n{} {}\n255\n", width, height)?;
+        file.write_all(&pixels)?;
+        file.flush()
+    }
+
+    /// Writes the frame as a PNG image, drawing each cell as a `scale` x `scale` square
+    pub fn write_png(&self, path: impl AsRef<Path>, scale: usize, palette: impl Fn(char) -> [u8; 3]) -> Result<(), Box<dyn Error>> {
+        let (width, height, pixels) = self.to_rgb(scale, palette);
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+        Ok(())
+    }
+}
+
+/// Renders the frame as plain text, one line per row
+impl<T> Display for Frame<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self.cells.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// A palette suitable for most puzzle grids: blanks (`.` and space) are black, walls and
+/// lit pixels (`#` and `█`) are white, and any other glyph gets a bright colour of its own.
+pub fn default_palette(glyph: char) -> [u8; 3] {
+    const COLOURS: [[u8; 3]; 6] = [
+        [230, 25, 75], [60, 180, 75], [255, 225, 25], [0, 130, 200], [245, 130, 48], [145, 30, 180]
+    ];
+    match glyph {
+        '.' | ' ' => [0, 0, 0],
+        '#' | '█' => [255, 255, 255],
+        c => COLOURS[c as usize % COLOURS.len()]
+    }
+}
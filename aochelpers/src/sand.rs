@@ -0,0 +1,188 @@
+use std::{collections::{HashMap, HashSet}, error::Error, fmt::{self, Display}};
+use crate::{Coordinate, Direction, Frame, Rectangle};
+
+/// What lies beneath the rocks in a `SandSimulation`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Bottom {
+    /// Grains which fall below the given `y` are lost forever
+    Abyss(i32),
+    /// An infinitely wide solid floor at the given `y`
+    Floor(i32)
+}
+
+/// What happened to a grain dropped into a `SandSimulation`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Grain {
+    /// The grain came to rest at this point
+    Settled(Coordinate<i32>),
+    /// The grain fell into the abyss
+    Lost,
+    /// The spawn point is already filled, so no grain could be dropped
+    Blocked
+}
+
+/// Returned when a `SandSimulation` is given moves it can't use
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SandError {
+    /// Grains could move back and forth forever unless every move takes them further south
+    NotSouthward(Direction)
+}
+
+impl Display for SandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SandError::NotSouthward(direction) => write!(f, "Grains can only move South, SouthEast or SouthWest, not {:?}", direction)
+        }
+    }
+}
+
+impl Error for SandError {}
+
+/// Grains of sand (or any other particles) falling through a cave of rocks and coming to
+/// rest, as in 2022 day 14.
+///
+/// Each grain tries the moves in order of preference, taking the first which leads to an
+/// empty space, and settles when none do. By default grains fall from (500, 0), moving down,
+/// then down-left, then down-right, and anything falling past the lowest rock is lost.
+///
+/// Rather than following each grain from the top, the simulation remembers the path taken by
+/// the previous grain from each spawn point. The next grain follows the same path until just
+/// before the point where its predecessor settled, so only the last few moves are recalculated.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::{Bottom, Coordinate, SandSimulation};
+/// let rocks = (-2..=2).map(|x| Coordinate { x: 500 + x, y: 3 });
+/// let mut sand = SandSimulation::new(rocks);
+/// assert_eq!(sand.fill(), 4);
+/// assert_eq!(sand.to_string(), "..+..\n..o..\n.ooo.\n#####");
+/// let mut sand = sand.with_bottom(Bottom::Floor(5));
+/// sand.fill();
+/// assert_eq!(sand.settled().len(), 17);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandSimulation {
+    rocks: HashSet<Coordinate<i32>>,
+    settled: HashSet<Coordinate<i32>>,
+    spawn: Coordinate<i32>,
+    moves: Vec<Direction>,
+    bottom: Bottom,
+    paths: HashMap<Coordinate<i32>, Vec<Coordinate<i32>>>
+}
+
+impl SandSimulation {
+    pub fn new(rocks: impl IntoIterator<Item = Coordinate<i32>>) -> Self {
+        let rocks = rocks.into_iter().collect::<HashSet<_>>();
+        let lowest = rocks.iter().map(|r| r.y).max().unwrap_or(0);
+        SandSimulation {
+            rocks,
+            settled: HashSet::new(),
+            spawn: Coordinate { x: 500, y: 0 },
+            moves: vec![Direction::South, Direction::SouthWest, Direction::SouthEast],
+            bottom: Bottom::Abyss(lowest),
+            paths: HashMap::new()
+        }
+    }
+
+    /// Sets where `drop_grain()` and `fill()` drop grains from
+    pub fn with_spawn(mut self, spawn: Coordinate<i32>) -> Self {
+        self.spawn = spawn;
+        self
+    }
+
+    /// Sets the moves grains try, most preferred first. Every move must take grains further
+    /// south, so that each one is sure to settle or be lost.
+    pub fn with_moves(mut self, moves: &[Direction]) -> Result<Self, SandError> {
+        if let Some(direction) = moves.iter().find(|m| !matches!(m, Direction::South | Direction::SouthEast | Direction::SouthWest)) {
+            return Err(SandError::NotSouthward(*direction));
+        }
+        self.moves = moves.to_vec();
+        self.paths.clear();
+        Ok(self)
+    }
+
+    pub fn with_bottom(mut self, bottom: Bottom) -> Self {
+        self.bottom = bottom;
+        self.paths.clear();
+        self
+    }
+
+    pub fn rocks(&self) -> &HashSet<Coordinate<i32>> {
+        &self.rocks
+    }
+
+    /// Every point where a grain has come to rest
+    pub fn settled(&self) -> &HashSet<Coordinate<i32>> {
+        &self.settled
+    }
+
+    /// Is the point filled by rock, sand or the floor?
+    pub fn is_filled(&self, point: &Coordinate<i32>) -> bool {
+        self.rocks.contains(point) || self.settled.contains(point) || matches!(self.bottom, Bottom::Floor(y) if point.y >= y)
+    }
+
+    /// Drops a single grain from the spawn point
+    pub fn drop_grain(&mut self) -> Grain {
+        self.drop_from(self.spawn)
+    }
+
+    /// Drops a single grain from the given point
+    pub fn drop_from(&mut self, spawn: Coordinate<i32>) -> Grain {
+        if self.is_filled(&spawn) {
+            return Grain::Blocked;
+        }
+        let mut path = self.paths.remove(&spawn).unwrap_or_default();
+        // Grains from other spawn points may have landed on the remembered path since it was taken
+        let still_open = path.iter().position(|p| self.is_filled(p)).unwrap_or(path.len());
+        path.truncate(still_open);
+        if path.is_empty() {
+            path.push(spawn);
+        }
+        let result = loop {
+            let current = *path.last().unwrap();
+            match self.moves.iter().map(|m| current.neighbour(*m)).find(|n| !self.is_filled(n)) {
+                Some(next) if matches!(self.bottom, Bottom::Abyss(y) if next.y > y) => break Grain::Lost,
+                Some(next) => path.push(next),
+                None => {
+                    path.pop();
+                    self.settled.insert(current);
+                    break Grain::Settled(current);
+                }
+            }
+        };
+        self.paths.insert(spawn, path);
+        result
+    }
+
+    /// Drops grains from the spawn point until one is lost or the spawn point is blocked,
+    /// returning the number which settled
+    pub fn fill(&mut self) -> usize {
+        std::iter::from_fn(|| match self.drop_grain() {
+            Grain::Settled(point) => Some(point),
+            _ => None
+        }).count()
+    }
+
+    /// Draws the cave, with `#` for rock, `o` for settled grains and `+` for the spawn point
+    pub fn frame(&self) -> Frame<i32> {
+        let bounds = Rectangle::bounding_box(self.rocks.iter().chain(self.settled.iter()).chain([&self.spawn]))
+            .unwrap();
+        Frame::new(bounds, |p| {
+            if self.rocks.contains(p) {
+                '#'
+            } else if self.settled.contains(p) {
+                'o'
+            } else if *p == self.spawn {
+                '+'
+            } else {
+                '.'
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for SandSimulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.frame())
+    }
+}
@@ -0,0 +1,209 @@
+use std::{fmt::{self, Display}, error::Error, str::FromStr};
+use crate::parse_error::column_of;
+
+/// Extracts every integer from a line of text, in order, ignoring everything else.
+///
+/// A `-` is treated as a sign only when it isn't directly preceded by a letter or digit, so
+/// ranges such as `2-4` yield `2` and `4` rather than `2` and `-4`. Numbers which don't fit
+/// in `T` are skipped.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::ints;
+/// assert_eq!(ints::<i32>("Sensor at x=2, y=-18: beacon at x=-2, y=15"), vec![2, -18, -2, 15]);
+/// assert_eq!(ints::<u8>("2-4,6-8"), vec![2, 4, 6, 8]);
+/// ```
+pub fn ints<T: FromStr>(line: &str) -> Vec<T> {
+    let chars = line.char_indices().collect::<Vec<_>>();
+    let mut numbers = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let signed = c == '-' && chars.get(i + 1).is_some_and(|(_, d)| d.is_ascii_digit())
+            && (i == 0 || !chars[i - 1].1.is_alphanumeric());
+        if !signed && !c.is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end < chars.len() && chars[end].1.is_ascii_digit() {
+            end += 1;
+        }
+        let stop = chars.get(end).map(|(index, _)| *index).unwrap_or(line.len());
+        if let Ok(number) = line[start..stop].parse() {
+            numbers.push(number);
+        }
+        i = end;
+    }
+    numbers
+}
+
+/// Returned when a line doesn't match a `scan!` template, or one of its fields can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanError {
+    /// The line differs from the literal text of the template. `column` is the 1-based
+    /// character position in the line at which matching failed.
+    Mismatch { column: usize, expected: String, found: String },
+    /// The template has a different number of `{}` placeholders than the requested tuple has fields
+    FieldCount { template: usize, requested: usize },
+    /// The text captured by the `index`th placeholder (counting from 1), starting at `column`
+    /// in the line, couldn't be parsed
+    InvalidField { index: usize, column: usize, value: String, reason: String }
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::Mismatch { column, expected, found } =>
+                write!(f, "Expected {:?} at column {}, found {:?}", expected, column, found),
+            ScanError::FieldCount { template, requested } =>
+                write!(f, "Template has {} fields but {} were requested", template, requested),
+            ScanError::InvalidField { index, column, value, reason } =>
+                write!(f, "Couldn't parse field {} ({:?}) at column {}: {}", index, value, column, reason)
+        }
+    }
+}
+
+impl Error for ScanError {}
+
+/// A type which can be built from the fields captured by a `scan!` template: a tuple of up to
+/// eight `FromStr` types, or a `Vec` of a single type for any number of fields.
+pub trait FromFields: Sized {
+    fn from_fields(fields: &[&str]) -> Result<Self, ScanError>;
+}
+
+fn parse_field<T: FromStr>(fields: &[&str], index: usize) -> Result<T, ScanError> where <T as FromStr>::Err: Display {
+    fields[index].parse().map_err(|e: <T as FromStr>::Err| ScanError::InvalidField {
+        index: index + 1,
+        column: 0,
+        value: fields[index].to_string(),
+        reason: e.to_string()
+    })
+}
+
+impl<T: FromStr> FromFields for Vec<T> where <T as FromStr>::Err: Display {
+    fn from_fields(fields: &[&str]) -> Result<Self, ScanError> {
+        (0..fields.len()).map(|i| parse_field(fields, i)).collect()
+    }
+}
+
+macro_rules! from_fields_for_tuple {
+    ($count:expr; $($t:ident $i:tt),*) => {
+        impl<$($t: FromStr),*> FromFields for ($($t,)*) where $(<$t as FromStr>::Err: Display),* {
+            fn from_fields(fields: &[&str]) -> Result<Self, ScanError> {
+                if fields.len() != $count {
+                    return Err(ScanError::FieldCount { template: fields.len(), requested: $count });
+                }
+                Ok(($(parse_field::<$t>(fields, $i)?,)*))
+            }
+        }
+    };
+}
+
+from_fields_for_tuple!(1; A 0);
+from_fields_for_tuple!(2; A 0, B 1);
+from_fields_for_tuple!(3; A 0, B 1, C 2);
+from_fields_for_tuple!(4; A 0, B 1, C 2, D 3);
+from_fields_for_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+from_fields_for_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+from_fields_for_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+from_fields_for_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Attempts to match `literal` against `line` starting at byte offset `position`, returning the
+/// offset just past the match. Any run of whitespace in the literal matches any run of
+/// whitespace in the line.
+fn match_literal(line: &str, position: usize, literal: &str) -> Result<usize, usize> {
+    let mut rest = &line[position..];
+    fn skip_whitespace(rest: &str) -> Option<&str> {
+        let trimmed = rest.trim_start();
+        (trimmed.len() < rest.len()).then_some(trimmed)
+    }
+    for (i, word) in literal.split_whitespace().enumerate() {
+        if i > 0 || literal.starts_with(char::is_whitespace) {
+            rest = skip_whitespace(rest).ok_or(line.len() - rest.len())?;
+        }
+        rest = rest.strip_prefix(word).ok_or(line.len() - rest.len())?;
+    }
+    if literal.ends_with(char::is_whitespace) {
+        rest = skip_whitespace(rest).ok_or(line.len() - rest.len())?;
+    }
+    Ok(line.len() - rest.len())
+}
+
+/// Splits `line` into the text matched by each `{}` placeholder of `template`.
+///
+/// Each placeholder captures the shortest non-empty run of text which lets the rest of the
+/// template match, so placeholders should be separated by some literal text. Captured text
+/// and the ends of the line are trimmed of whitespace.
+pub fn scan_fields<'a>(template: &str, line: &'a str) -> Result<Vec<&'a str>, ScanError> {
+    let untrimmed = line;
+    let line = line.trim();
+    let literals = template.trim().split("{}").collect::<Vec<_>>();
+    let mismatch = |position: usize, expected: &str| ScanError::Mismatch {
+        column: column_of(untrimmed, &line[position..]),
+        expected: expected.trim().to_string(),
+        found: line[position..].chars().take(expected.trim().len().max(10)).collect()
+    };
+
+    let mut position = match_literal(line, 0, literals[0]).map_err(|p| mismatch(p, literals[0]))?;
+    let mut fields = Vec::new();
+    for (i, literal) in literals.iter().enumerate().skip(1) {
+        let last = i == literals.len() - 1;
+        let field_end = if last && literal.trim().is_empty() {
+            Some((line.len(), line.len()))
+        } else {
+            line[position..].char_indices().skip(1).map(|(offset, _)| position + offset)
+                .chain(std::iter::once(line.len()))
+                .find_map(|start| match_literal(line, start, literal).ok()
+                    .filter(|end| !last || *end == line.len())
+                    .map(|end| (start, end)))
+        };
+        let Some((start, end)) = field_end else {
+            return Err(mismatch(position, literal));
+        };
+        if line[position..start].trim().is_empty() {
+            return Err(mismatch(position, "a value"));
+        }
+        fields.push(line[position..start].trim());
+        position = end;
+    }
+    if position != line.len() {
+        return Err(mismatch(position, "end of line"));
+    }
+    Ok(fields)
+}
+
+/// Matches `line` against `template` and parses the captured fields; used by `scan!`
+pub fn scan_str<T: FromFields>(template: &str, line: &str) -> Result<T, ScanError> {
+    let fields = scan_fields(template, line)?;
+    T::from_fields(&fields).map_err(|error| match error {
+        ScanError::InvalidField { index, value, reason, .. } =>
+            ScanError::InvalidField { index, column: column_of(line, fields[index - 1]), value, reason },
+        error => error
+    })
+}
+
+/// Matches a line against a template containing `{}` placeholders, parsing the captured text
+/// into a tuple (or `Vec`) of the requested types. See `scan_fields()` for the matching rules.
+///
+/// Whitespace in the template matches any run of whitespace in the line, so
+/// indentation and doubled spaces don't affect parsing.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::scan;
+/// let (count, from, to): (usize, usize, usize) = scan!("move {} from {} to {}", "move 3 from  1 to 2").unwrap();
+/// assert_eq!((count, from, to), (3, 1, 2));
+/// let (name, size) = scan!("{} {}", "dir a" => String, String).unwrap();
+/// assert_eq!(name, "dir");
+/// assert_eq!(size, "a");
+/// ```
+#[macro_export]
+macro_rules! scan {
+    ($template:expr, $line:expr) => {
+        $crate::scan_str($template, $line)
+    };
+    ($template:expr, $line:expr => $($t:ty),+) => {
+        $crate::scan_str::<($($t,)+)>($template, $line)
+    };
+}
@@ -0,0 +1,189 @@
+use std::{fmt::{self, Display}, error::Error, str::FromStr};
+use crate::{scan, ParseError, parse_error::column_of};
+
+/// A single crane instruction, parsed from `move 3 from 1 to 2`. Stacks are numbered from 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CraneMove {
+    pub quantity: usize,
+    pub origin: usize,
+    pub destination: usize
+}
+
+impl FromStr for CraneMove {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (quantity, origin, destination) = scan!("move {} from {} to {}", s => usize, usize, usize)?;
+        Ok(CraneMove { quantity, origin, destination })
+    }
+}
+
+impl Display for CraneMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.quantity, self.origin, self.destination)
+    }
+}
+
+/// Returned when a `CraneMove` can't be carried out. `instruction` is the 1-based position of
+/// the move within the list passed to `CrateStacks::apply_all()`, or `None` for a single move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackError {
+    /// The move refers to a stack which doesn't exist
+    NoSuchStack { instruction: Option<usize>, stack: usize },
+    /// The move tries to take more crates than the origin stack holds
+    NotEnoughCrates { instruction: Option<usize>, stack: usize, requested: usize, available: usize }
+}
+
+impl StackError {
+    fn on_instruction(self, number: usize) -> Self {
+        match self {
+            StackError::NoSuchStack { stack, .. } => StackError::NoSuchStack { instruction: Some(number), stack },
+            StackError::NotEnoughCrates { stack, requested, available, .. } =>
+                StackError::NotEnoughCrates { instruction: Some(number), stack, requested, available }
+        }
+    }
+}
+
+impl Display for StackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (StackError::NoSuchStack { instruction, .. } | StackError::NotEnoughCrates { instruction, .. }) = self;
+        if let Some(instruction) = instruction {
+            write!(f, "Instruction {}: ", instruction)?;
+        }
+        match self {
+            StackError::NoSuchStack { stack, .. } => write!(f, "There is no stack {}", stack),
+            StackError::NotEnoughCrates { stack, requested, available, .. } =>
+                write!(f, "Can't take {} crates from stack {}, which only has {}", requested, stack, available)
+        }
+    }
+}
+
+impl Error for StackError {}
+
+/// How a crane moves a group of crates between stacks
+pub trait CraneModel {
+    /// Places the crates `lifted` off the top of one stack (listed bottom to top) onto `destination`
+    fn place(&self, lifted: Vec<char>, destination: &mut Vec<char>);
+}
+
+/// A crane which moves crates one at a time, reversing their order (the CrateMover 9000)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct SingleMove;
+
+impl CraneModel for SingleMove {
+    fn place(&self, lifted: Vec<char>, destination: &mut Vec<char>) {
+        destination.extend(lifted.into_iter().rev());
+    }
+}
+
+/// A crane which moves several crates at once, keeping their order (the CrateMover 9001)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct BatchMove;
+
+impl CraneModel for BatchMove {
+    fn place(&self, lifted: Vec<char>, destination: &mut Vec<char>) {
+        destination.extend(lifted);
+    }
+}
+
+/// Stacks of crates, each listed bottom to top, as drawn in 2022 day 5.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::{BatchMove, CraneMove, CrateStacks, SingleMove};
+/// let drawing = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+/// let mut stacks = drawing.parse::<CrateStacks>().unwrap();
+/// let moves = ["move 1 from 2 to 1", "move 3 from 1 to 3"].map(|m| m.parse::<CraneMove>().unwrap());
+/// let mut batched = stacks.clone();
+/// stacks.apply_all(&SingleMove, &moves).unwrap();
+/// batched.apply_all(&BatchMove, &moves).unwrap();
+/// assert_eq!((stacks.tops(), batched.tops()), ("CZ".to_string(), "CD".to_string()));
+/// assert!(stacks.apply(&SingleMove, &"move 1 from 1 to 2".parse().unwrap()).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CrateStacks {
+    pub stacks: Vec<Vec<char>>
+}
+
+impl CrateStacks {
+    /// Carries out a single move. If it can't be done, the stacks are left unchanged.
+    pub fn apply(&mut self, crane: &impl CraneModel, instruction: &CraneMove) -> Result<(), StackError> {
+        for stack in [instruction.origin, instruction.destination] {
+            if stack == 0 || stack > self.stacks.len() {
+                return Err(StackError::NoSuchStack { instruction: None, stack });
+            }
+        }
+        let origin = &mut self.stacks[instruction.origin - 1];
+        if origin.len() < instruction.quantity {
+            return Err(StackError::NotEnoughCrates {
+                instruction: None,
+                stack: instruction.origin,
+                requested: instruction.quantity,
+                available: origin.len()
+            });
+        }
+        let lifted = origin.split_off(origin.len() - instruction.quantity);
+        crane.place(lifted, &mut self.stacks[instruction.destination - 1]);
+        Ok(())
+    }
+
+    /// Carries out each move in turn, stopping at the first which can't be done
+    pub fn apply_all(&mut self, crane: &impl CraneModel, instructions: &[CraneMove]) -> Result<(), StackError> {
+        for (i, instruction) in instructions.iter().enumerate() {
+            self.apply(crane, instruction).map_err(|e| e.on_instruction(i + 1))?;
+        }
+        Ok(())
+    }
+
+    /// The crate on top of each stack, skipping empty stacks
+    pub fn tops(&self) -> String {
+        self.stacks.iter().filter_map(|s| s.last()).collect()
+    }
+}
+
+/// Parses a drawing of stacked `[X]` crates above a footer line numbering the stacks.
+///
+/// The number of stacks, and where each one is drawn, are taken from the footer, so any number
+/// of stacks and any spacing will do. Crates which aren't above a stack number, or which are
+/// floating above an empty space, are reported as errors.
+impl FromStr for CrateStacks {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.lines().collect::<Vec<_>>();
+        let footer_index = lines.iter().rposition(|l| !l.trim().is_empty())
+            .ok_or_else(|| ParseError::new(0, s, "a drawing of numbered stacks"))?;
+        let footer = lines[footer_index];
+        let mut columns = Vec::new();
+        for label in footer.split_whitespace() {
+            if label.parse::<usize>() != Ok(columns.len() + 1) {
+                return Err(ParseError::at(footer, label, format!("stack number {}", columns.len() + 1)).on_line(footer_index + 1));
+            }
+            columns.push(column_of(footer, label) - 1);
+        }
+        if columns.is_empty() {
+            return Err(ParseError::at(footer, footer, "stack numbers").on_line(footer_index + 1));
+        }
+
+        let mut stacks = vec![Vec::new(); columns.len()];
+        for (line_index, line) in lines[..footer_index].iter().enumerate().rev() {
+            let chars = line.chars().collect::<Vec<_>>();
+            for (offset, c) in line.char_indices().filter(|(_, c)| !c.is_whitespace()) {
+                let column = line[..offset].chars().count();
+                let fail = |expected: &str| ParseError::at(line, &line[offset..offset + c.len_utf8()], expected).on_line(line_index + 1);
+                let aligned = columns.iter().any(|col| column + 1 == *col || column == *col || column == col + 1);
+                if !aligned {
+                    return Err(fail("a crate above a numbered stack"));
+                }
+                if let Some(stack) = columns.iter().position(|col| *col == column) {
+                    if chars.get(column.wrapping_sub(1)) != Some(&'[') || chars.get(column + 1) != Some(&']') {
+                        return Err(fail("a crate drawn as [X]"));
+                    }
+                    if stacks[stack].len() != footer_index - 1 - line_index {
+                        return Err(fail("a crate resting on another crate"));
+                    }
+                    stacks[stack].push(c);
+                }
+            }
+        }
+        Ok(CrateStacks { stacks })
+    }
+}
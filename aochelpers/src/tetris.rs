@@ -0,0 +1,327 @@
+use std::{collections::{HashMap, hash_map::Entry}, fmt::{self, Display}, error::Error, str::FromStr};
+use crate::{numbered_sections, Coordinate, Direction, ParseError};
+
+/// The rocks of 2022 day 17, in the order they fall
+pub const STANDARD_PIECES: &str = "####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+
+/// The most pieces `TetrisChamber::height_after()` will simulate while looking for the top of
+/// the tower to repeat
+pub const MAX_SIMULATED_PIECES: u64 = 100_000;
+
+/// The deepest top of the tower `TetrisChamber::height_after()` will remember. Deeper tops are
+/// left out of the search for a repeat, which keeps chambers with an unfilled shaft from using
+/// ever more time and memory.
+const MAX_SURFACE_DEPTH: usize = 128;
+
+/// Returned when a `TetrisChamber` can't be set up, or can't work out a height
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TetrisError {
+    NoPieces,
+    NoJets,
+    /// Jets can only push `East` or `West`
+    InvalidJet(Direction),
+    /// Chambers must be between 1 and 64 cells wide
+    InvalidWidth(usize),
+    /// The piece at this index doesn't fit between the walls where pieces appear
+    PieceDoesNotFit(usize),
+    /// The top of the tower didn't repeat within `MAX_SIMULATED_PIECES` pieces
+    NoCycle
+}
+
+impl Display for TetrisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TetrisError::NoPieces => write!(f, "At least one piece is needed"),
+            TetrisError::NoJets => write!(f, "At least one jet is needed"),
+            TetrisError::InvalidJet(direction) => write!(f, "Jets can only push East or West, not {:?}", direction),
+            TetrisError::InvalidWidth(width) => write!(f, "A chamber {} cells wide isn't between 1 and 64 cells wide", width),
+            TetrisError::PieceDoesNotFit(index) => write!(f, "Piece {} doesn't fit in the chamber where pieces appear", index),
+            TetrisError::NoCycle => write!(f, "The tower didn't repeat within {} pieces", MAX_SIMULATED_PIECES)
+        }
+    }
+}
+
+impl Error for TetrisError {}
+
+/// A shape dropped into a `TetrisChamber`, stored as one bitmask per row from the bottom up,
+/// with bit 0 as the leftmost column.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Piece {
+    rows: Vec<u64>,
+    width: usize
+}
+
+impl Piece {
+    /// The five rocks of 2022 day 17
+    pub fn standard() -> Vec<Piece> {
+        parse_pieces(STANDARD_PIECES).unwrap()
+    }
+
+    /// Each row of the piece from the bottom up
+    pub fn rows(&self) -> &[u64] {
+        &self.rows
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+}
+
+/// Parses a piece drawn with `#` for filled cells and `.` for gaps, top row first. Empty rows
+/// and columns around the edge of the drawing are trimmed off.
+impl FromStr for Piece {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows = Vec::new();
+        for (line_index, line) in s.lines().enumerate() {
+            let mut row = 0_u64;
+            for (column, c) in line.trim_end().chars().enumerate() {
+                match c {
+                    '#' if column < 64 => row |= 1 << column,
+                    '#' => return Err(ParseError::new(column + 1, c, "a piece at most 64 cells wide").on_line(line_index + 1)),
+                    '.' => {},
+                    _ => return Err(ParseError::new(column + 1, c, "# or .").on_line(line_index + 1))
+                }
+            }
+            rows.push(row);
+        }
+        let top = rows.iter().position(|r| *r != 0)
+            .ok_or_else(|| ParseError::new(0, s, "a piece with at least one # cell"))?;
+        let bottom = rows.iter().rposition(|r| *r != 0).unwrap();
+        let left = rows.iter().map(|r| r.trailing_zeros()).min().unwrap();
+        let rows = rows[top..=bottom].iter().rev().map(|r| r >> left).collect::<Vec<_>>();
+        let width = rows.iter().map(|r| 64 - r.leading_zeros() as usize).max().unwrap();
+        Ok(Piece { rows, width })
+    }
+}
+
+/// Parses pieces drawn one after another, separated by blank lines
+pub fn parse_pieces(s: &str) -> Result<Vec<Piece>, ParseError> {
+    numbered_sections(s).iter().map(|(first_line, section)| {
+        section.parse::<Piece>().map_err(|e| match e.line {
+            0 => e,
+            line => e.on_line(first_line + line - 1)
+        })
+    }).collect()
+}
+
+/// Parses a line of `<` and `>` jets into `West` and `East` pushes, ignoring surrounding whitespace
+pub fn parse_jets(s: &str) -> Result<Vec<Direction>, ParseError> {
+    let line = s.trim();
+    line.char_indices().map(|(offset, c)| match c {
+        '<' => Ok(Direction::West),
+        '>' => Ok(Direction::East),
+        _ => Err(ParseError::at(line, &line[offset..offset + c.len_utf8()], "< or >"))
+    }).collect()
+}
+
+/// A narrow chamber which pieces fall into one at a time, as in 2022 day 17.
+///
+/// Pieces are dropped in turn, each appearing with its left edge `spawn.x` cells from the left
+/// wall and its bottom edge `spawn.y` cells above the top of the tower. A falling piece is
+/// pushed sideways by the next jet (unless that would hit a wall or another piece), then falls
+/// one cell, and comes to rest when it can't fall any further. By default the chamber is 7
+/// cells wide and pieces appear at (2, 3).
+///
+/// Once the pieces, jets and the top of the tower all line up with an earlier drop the tower
+/// grows in a repeating cycle, which `height_after()` uses to skip ahead to any number of pieces.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::{parse_jets, Coordinate, Piece, TetrisChamber, TetrisError};
+/// let jets = parse_jets(">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>").unwrap();
+/// let mut chamber = TetrisChamber::new(Piece::standard(), jets.clone()).unwrap();
+/// chamber.drop_piece();
+/// chamber.drop_piece();
+/// assert_eq!(chamber.to_string(), "|...#...|\n|..###..|\n|...#...|\n|..####.|\n+-------+");
+/// assert_eq!(chamber.height_after(2022), Ok(3068));
+/// assert_eq!(chamber.height_after(1_000_000_000_000), Ok(1514285714288));
+/// // The four-wide piece can't appear 4 cells from the wall of a 7-wide chamber
+/// let layout = TetrisChamber::with_layout(Piece::standard(), jets, 7, Coordinate { x: 4, y: 3 });
+/// assert_eq!(layout, Err(TetrisError::PieceDoesNotFit(0)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TetrisChamber {
+    pieces: Vec<Piece>,
+    jets: Vec<Direction>,
+    width: usize,
+    spawn: Coordinate<usize>,
+    rows: Vec<u64>,
+    next_piece: usize,
+    next_jet: usize,
+    heights: Vec<u64>
+}
+
+impl TetrisChamber {
+    pub fn new(pieces: Vec<Piece>, jets: Vec<Direction>) -> Result<Self, TetrisError> {
+        Self::with_layout(pieces, jets, 7, Coordinate { x: 2, y: 3 })
+    }
+
+    /// Creates a chamber `width` cells wide (from 1 to 64), in which pieces appear `spawn.x`
+    /// cells from the left wall and `spawn.y` cells above the tower. Every piece must fit
+    /// between the walls where it appears.
+    pub fn with_layout(pieces: Vec<Piece>, jets: Vec<Direction>, width: usize, spawn: Coordinate<usize>) -> Result<Self, TetrisError> {
+        if pieces.is_empty() {
+            return Err(TetrisError::NoPieces);
+        }
+        if jets.is_empty() {
+            return Err(TetrisError::NoJets);
+        }
+        if let Some(jet) = jets.iter().find(|j| !matches!(j, Direction::East | Direction::West)) {
+            return Err(TetrisError::InvalidJet(*jet));
+        }
+        if !(1..=64).contains(&width) {
+            return Err(TetrisError::InvalidWidth(width));
+        }
+        if let Some(index) = pieces.iter().position(|p| spawn.x + p.width > width) {
+            return Err(TetrisError::PieceDoesNotFit(index));
+        }
+        Ok(TetrisChamber { pieces, jets, width, spawn, rows: Vec::new(), next_piece: 0, next_jet: 0, heights: vec![0] })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the tower
+    pub fn height(&self) -> u64 {
+        self.rows.len() as u64
+    }
+
+    /// The number of pieces dropped so far
+    pub fn dropped(&self) -> u64 {
+        self.heights.len() as u64 - 1
+    }
+
+    /// Each row of the tower from the bottom up, with bit 0 as the leftmost column
+    pub fn rows(&self) -> &[u64] {
+        &self.rows
+    }
+
+    fn fits(&self, piece: &Piece, x: usize, y: usize) -> bool {
+        x + piece.width <= self.width && piece.rows.iter().enumerate()
+            .all(|(i, row)| self.rows.get(y + i).is_none_or(|r| r & row << x == 0))
+    }
+
+    /// Drops the next piece, returning where its bottom left corner came to rest
+    pub fn drop_piece(&mut self) -> Coordinate<usize> {
+        let piece = self.pieces[self.next_piece].clone();
+        self.next_piece = (self.next_piece + 1) % self.pieces.len();
+        let (mut x, mut y) = (self.spawn.x, self.rows.len() + self.spawn.y);
+        loop {
+            let jet = self.jets[self.next_jet];
+            self.next_jet = (self.next_jet + 1) % self.jets.len();
+            let pushed = match jet {
+                Direction::West => x.checked_sub(1),
+                _ => Some(x + 1)
+            };
+            if let Some(pushed) = pushed.filter(|p| self.fits(&piece, *p, y)) {
+                x = pushed;
+            }
+            if y == 0 || !self.fits(&piece, x, y - 1) {
+                break;
+            }
+            y -= 1;
+        }
+        if self.rows.len() < y + piece.height() {
+            self.rows.resize(y + piece.height(), 0);
+        }
+        for (i, row) in piece.rows.iter().enumerate() {
+            self.rows[y + i] |= row << x;
+        }
+        self.heights.push(self.height());
+        Coordinate { x, y }
+    }
+
+    /// The rows at the top of the tower which falling pieces can reach or touch, from the top
+    /// down, and whether the floor can be reached. Nothing below these rows can change what
+    /// happens to later pieces. Gives up if there are more than `MAX_SURFACE_DEPTH` such rows.
+    fn surface(&self) -> Option<(Vec<u64>, bool)> {
+        let full = u64::MAX >> (64 - self.width);
+        let mut reachable = full;
+        let mut surface = Vec::new();
+        for row in self.rows.iter().rev() {
+            if reachable == 0 {
+                break;
+            }
+            if surface.len() == MAX_SURFACE_DEPTH {
+                return None;
+            }
+            let empty = !row & full;
+            let mut spread = reachable & empty;
+            loop {
+                let wider = (spread | spread << 1 | spread >> 1) & empty;
+                if wider == spread {
+                    break;
+                }
+                spread = wider;
+            }
+            surface.push(*row);
+            reachable = spread;
+        }
+        Some((surface, reachable != 0))
+    }
+
+    /// The height of the tower once `pieces` pieces have been dropped in total. Earlier heights
+    /// are remembered; later ones are simulated (without changing this chamber) until the top
+    /// of the tower repeats, after which the rest are worked out from the cycle. If some part of
+    /// the chamber is never filled the top of the tower can't repeat, so this gives up with
+    /// `TetrisError::NoCycle` once `MAX_SIMULATED_PIECES` have been simulated.
+    pub fn height_after(&self, pieces: u64) -> Result<u64, TetrisError> {
+        if pieces <= self.dropped() {
+            return Ok(self.heights[pieces as usize]);
+        }
+        let mut chamber = self.clone();
+        let mut seen = HashMap::new();
+        while chamber.dropped() < pieces {
+            if let Some(surface) = chamber.surface() {
+                match seen.entry((chamber.next_piece, chamber.next_jet, surface)) {
+                    Entry::Occupied(earlier) => {
+                        let start = *earlier.get();
+                        let length = chamber.dropped() - start;
+                        let growth = chamber.height() - chamber.heights[start as usize];
+                        let (cycles, remainder) = ((pieces - start) / length, (pieces - start) % length);
+                        return Ok(chamber.heights[(start + remainder) as usize] + cycles * growth);
+                    },
+                    Entry::Vacant(entry) => { entry.insert(chamber.dropped()); }
+                }
+            }
+            if chamber.dropped() - self.dropped() >= MAX_SIMULATED_PIECES {
+                return Err(TetrisError::NoCycle);
+            }
+            chamber.drop_piece();
+        }
+        Ok(chamber.height())
+    }
+}
+
+/// Draws the tower top row first between `|` walls, with `#` for settled pieces
+impl Display for TetrisChamber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows.iter().rev() {
+            let cells = (0..self.width).map(|x| if row & 1 << x != 0 { '#' } else { '.' }).collect::<String>();
+            writeln!(f, "|{}|", cells)?;
+        }
+        write!(f, "+{}+", "-".repeat(self.width))
+    }
+}
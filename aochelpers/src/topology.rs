@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use crate::{Coordinate, CubeNet, Direction, Particle, Rectangle};
+
+/// Describes how a grid is connected at its edges, so that walkers and `Particle`s can step
+/// off an edge without each puzzle hard-coding the arithmetic.
+///
+/// Positions are `Coordinate<i32>`s with y increasing southwards. Stepping can change the
+/// facing (e.g. when crossing onto another face of a cube), so both are returned.
+pub trait Topology {
+    /// Takes one step from `position` towards `facing`. Returns `None` if the step leaves the grid,
+    /// or the topology doesn't support stepping in that direction.
+    fn step(&self, position: Coordinate<i32>, facing: Direction) -> Option<(Coordinate<i32>, Direction)>;
+
+    /// Takes `steps` steps, ignoring obstacles. Returns `None` if any of them leaves the grid.
+    fn advance(&self, position: Coordinate<i32>, facing: Direction, steps: usize) -> Option<(Coordinate<i32>, Direction)> {
+        (0..steps).try_fold((position, facing), |(position, facing), _| self.step(position, facing))
+    }
+
+    /// Walks up to `steps` steps, stopping early in front of the first square which isn't open
+    /// or at the edge of the grid. Returns where the walker ends up, and which way it faces.
+    fn walk(&self, position: Coordinate<i32>, facing: Direction, steps: usize, is_open: impl Fn(&Coordinate<i32>) -> bool) -> (Coordinate<i32>, Direction) where Self: Sized {
+        let mut current = (position, facing);
+        for _ in 0..steps {
+            match self.step(current.0, current.1) {
+                Some(next) if is_open(&next.0) => current = next,
+                _ => break
+            }
+        }
+        current
+    }
+}
+
+fn delta(direction: Direction) -> (i64, i64) {
+    let step = Coordinate { x: 0, y: 0 }.neighbour(direction);
+    (step.x, step.y)
+}
+
+/// A rectangular grid with hard edges: stepping outside the `Rectangle` (inclusive) fails.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Bounded(pub Rectangle<i32>);
+
+impl Topology for Bounded {
+    fn step(&self, position: Coordinate<i32>, facing: Direction) -> Option<(Coordinate<i32>, Direction)> {
+        Some((position.neighbour(facing), facing)).filter(|(next, _)| self.0.contains(next))
+    }
+
+    fn advance(&self, position: Coordinate<i32>, facing: Direction, steps: usize) -> Option<(Coordinate<i32>, Direction)> {
+        let (dx, dy) = delta(facing);
+        let x = i32::try_from(position.x as i64 + dx * steps as i64).ok()?;
+        let y = i32::try_from(position.y as i64 + dy * steps as i64).ok()?;
+        Some((Coordinate { x, y }, facing)).filter(|(next, _)| self.0.contains(next))
+    }
+}
+
+/// A rectangular grid (inclusive of its corners) whose opposite edges are joined, so leaving
+/// one side re-enters from the other, as with the blizzards of 2022 day 24.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Torus(pub Rectangle<i32>);
+
+impl Topology for Torus {
+    fn step(&self, position: Coordinate<i32>, facing: Direction) -> Option<(Coordinate<i32>, Direction)> {
+        self.advance(position, facing, 1)
+    }
+
+    /// Moves any distance in constant time
+    fn advance(&self, position: Coordinate<i32>, facing: Direction, steps: usize) -> Option<(Coordinate<i32>, Direction)> {
+        let Rectangle { top_left, bottom_right } = self.0;
+        let (dx, dy) = delta(facing);
+        let wrap = |value: i32, delta: i64, low: i32, high: i32| {
+            let size = (high - low + 1) as i64;
+            (low as i64 + (value as i64 - low as i64 + delta * (steps as i64 % size)).rem_euclid(size)) as i32
+        };
+        Some((Coordinate {
+            x: wrap(position.x, dx, top_left.x, bottom_right.x),
+            y: wrap(position.y, dy, top_left.y, bottom_right.y)
+        }, facing))
+    }
+}
+
+/// An irregularly shaped grid where each row and column is a single unbroken run of tiles, and
+/// stepping off the end of a row or column wraps round to its other end, as in part 1 of 2022 day 22.
+///
+/// Only North, East, South and West steps are supported.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::{Coordinate, Direction, RaggedWrap, Topology};
+/// let tiles = [(1, 0), (2, 0), (0, 1), (1, 1), (2, 1)].map(|(x, y)| Coordinate { x, y });
+/// let grid = RaggedWrap::new(&tiles);
+/// assert_eq!(grid.step(Coordinate { x: 2, y: 0 }, Direction::East), Some((Coordinate { x: 1, y: 0 }, Direction::East)));
+/// assert_eq!(grid.step(Coordinate { x: 1, y: 1 }, Direction::South), Some((Coordinate { x: 1, y: 0 }, Direction::South)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaggedWrap {
+    /// First and last x of each row
+    pub rows: HashMap<i32, (i32, i32)>,
+    /// First and last y of each column
+    pub columns: HashMap<i32, (i32, i32)>
+}
+
+impl RaggedWrap {
+    pub fn new<'a>(tiles: impl IntoIterator<Item = &'a Coordinate<i32>>) -> Self {
+        let mut rows: HashMap<i32, (i32, i32)> = HashMap::new();
+        let mut columns: HashMap<i32, (i32, i32)> = HashMap::new();
+        for tile in tiles {
+            let row = rows.entry(tile.y).or_insert((tile.x, tile.x));
+            *row = (row.0.min(tile.x), row.1.max(tile.x));
+            let column = columns.entry(tile.x).or_insert((tile.y, tile.y));
+            *column = (column.0.min(tile.y), column.1.max(tile.y));
+        }
+        RaggedWrap { rows, columns }
+    }
+}
+
+impl Topology for RaggedWrap {
+    fn step(&self, position: Coordinate<i32>, facing: Direction) -> Option<(Coordinate<i32>, Direction)> {
+        let &(first_x, last_x) = self.rows.get(&position.y)?;
+        let &(first_y, last_y) = self.columns.get(&position.x)?;
+        let next = match facing {
+            Direction::East if position.x == last_x => Coordinate { x: first_x, y: position.y },
+            Direction::West if position.x == first_x => Coordinate { x: last_x, y: position.y },
+            Direction::South if position.y == last_y => Coordinate { x: position.x, y: first_y },
+            Direction::North if position.y == first_y => Coordinate { x: position.x, y: last_y },
+            Direction::North | Direction::East | Direction::South | Direction::West => position.neighbour(facing),
+            _ => return None
+        };
+        Some((next, facing))
+    }
+}
+
+/// Steps off the edge of a face continue onto the adjacent face of the folded cube
+impl Topology for CubeNet {
+    fn step(&self, position: Coordinate<i32>, facing: Direction) -> Option<(Coordinate<i32>, Direction)> {
+        self.wrap(position, facing)
+    }
+}
+
+impl Particle<Direction> {
+    /// Where the particle is after moving `steps` squares from its starting point across the
+    /// given `Topology`, and which way it is then heading
+    pub fn position_after(&self, steps: usize, topology: &impl Topology) -> Option<(Coordinate<i32>, Direction)> {
+        topology.advance(self.starting_point, self.heading, steps)
+    }
+}
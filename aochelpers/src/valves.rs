@@ -0,0 +1,166 @@
+use std::{fmt::{self, Display}, error::Error, collections::{HashMap, VecDeque}};
+
+/// The largest number of working valves a `ValveNetwork` can schedule, as the work needed
+/// grows with 3 to the power of the number of valves
+pub const MAX_WORKING_VALVES: usize = 20;
+
+/// Returned when a `ValveNetwork` can't be built or scheduled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValveError {
+    /// No valve called `name` exists. `referenced_by` is the valve whose tunnel leads to it, if any.
+    UnknownValve { name: String, referenced_by: Option<String> },
+    /// There are more working valves than `MAX_WORKING_VALVES`
+    TooManyValves(usize),
+    /// More than one valve has this name
+    DuplicateValve(String)
+}
+
+impl Display for ValveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValveError::UnknownValve { name, referenced_by: Some(from) } => write!(f, "Valve {} has a tunnel to unknown valve {}", from, name),
+            ValveError::UnknownValve { name, referenced_by: None } => write!(f, "Unknown valve {}", name),
+            ValveError::TooManyValves(count) => write!(f, "{} working valves is too many to schedule (at most {})", count, MAX_WORKING_VALVES),
+            ValveError::DuplicateValve(name) => write!(f, "There is more than one valve called {}", name)
+        }
+    }
+}
+
+impl Error for ValveError {}
+
+/// Valves joined by tunnels, as in 2022 day 16. Moving through a tunnel takes a minute, as does
+/// opening a valve, after which it releases its flow rate in pressure every remaining minute.
+///
+/// Scheduling works on the valves with a positive flow rate only, using the shortest distances
+/// between them. The best pressure for every subset of those valves is worked out once, after
+/// which any number of agents can be given disjoint subsets to open.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::ValveNetwork;
+/// let network = ValveNetwork::new([
+///     ("AA", 0, vec!["BB", "CC"]), ("BB", 10, vec!["AA"]), ("CC", 1, vec!["AA"])
+/// ].map(|(name, rate, tunnels)| (name.to_string(), rate, tunnels.into_iter().map(String::from).collect()))).unwrap();
+/// // Alone: open BB at minute 2 and CC at minute 5, for 10 * 8 + 1 * 5
+/// assert_eq!(network.best_pressure("AA", 10, 1), Ok(85));
+/// // In a pair, both valves are open after 2 minutes
+/// assert_eq!(network.best_pressure("AA", 10, 2), Ok(88));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValveNetwork {
+    pub names: Vec<String>,
+    pub flow_rates: Vec<i64>,
+    /// The indices of the valves each valve's tunnels lead to
+    pub tunnels: Vec<Vec<usize>>
+}
+
+impl ValveNetwork {
+    /// Builds a network from `(name, flow rate, tunnels)` for each valve
+    pub fn new(valves: impl IntoIterator<Item = (String, i64, Vec<String>)>) -> Result<Self, ValveError> {
+        let valves = valves.into_iter().collect::<Vec<_>>();
+        let mut indices = HashMap::new();
+        for (i, (name, _, _)) in valves.iter().enumerate() {
+            if indices.insert(name.as_str(), i).is_some() {
+                return Err(ValveError::DuplicateValve(name.clone()));
+            }
+        }
+        let tunnels = valves.iter().map(|(name, _, tunnels)| tunnels.iter()
+            .map(|t| indices.get(t.as_str()).copied().ok_or_else(|| ValveError::UnknownValve { name: t.clone(), referenced_by: Some(name.clone()) }))
+            .collect::<Result<Vec<_>, _>>()
+        ).collect::<Result<Vec<_>, _>>()?;
+        let working = valves.iter().filter(|(_, rate, _)| *rate > 0).count();
+        if working > MAX_WORKING_VALVES {
+            return Err(ValveError::TooManyValves(working));
+        }
+        Ok(ValveNetwork {
+            names: valves.iter().map(|(name, _, _)| name.clone()).collect(),
+            flow_rates: valves.iter().map(|(_, rate, _)| *rate).collect(),
+            tunnels
+        })
+    }
+
+    pub fn index_of(&self, name: &str) -> Result<usize, ValveError> {
+        self.names.iter().position(|n| n == name).ok_or_else(|| ValveError::UnknownValve { name: name.to_string(), referenced_by: None })
+    }
+
+    /// The valves worth opening. Bit `i` of the subsets used by `best_by_subset()` refers to
+    /// the `i`th valve in this list.
+    pub fn working_valves(&self) -> Vec<usize> {
+        (0..self.names.len()).filter(|v| self.flow_rates[*v] > 0).collect()
+    }
+
+    /// The number of tunnels on the shortest route from `from` to every valve, or `None` for
+    /// valves which can't be reached
+    pub fn distances_from(&self, from: usize) -> Vec<Option<i64>> {
+        let mut distances = vec![None; self.names.len()];
+        distances[from] = Some(0);
+        let mut unvisited = VecDeque::from([from]);
+        while let Some(valve) = unvisited.pop_front() {
+            for next in self.tunnels[valve].iter() {
+                if distances[*next].is_none() {
+                    distances[*next] = distances[valve].map(|d| d + 1);
+                    unvisited.push_back(*next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// For every subset of the working valves (as a bitmask over `working_valves()`), the most
+    /// pressure a single agent starting at `start` can release in `time` minutes by opening
+    /// some or all of the valves in that subset.
+    pub fn best_by_subset(&self, start: &str, time: i64) -> Result<Vec<i64>, ValveError> {
+        let start = self.index_of(start)?;
+        let working = self.working_valves();
+        let distances = (0..self.names.len()).map(|v| self.distances_from(v)).collect::<Vec<_>>();
+        let mut best = vec![0; 1 << working.len()];
+        let mut pending = vec![(start, time, 0_usize, 0)];
+        while let Some((valve, time_left, opened, pressure)) = pending.pop() {
+            best[opened] = best[opened].max(pressure);
+            for (bit, next) in working.iter().enumerate().filter(|(bit, _)| opened & 1 << bit == 0) {
+                if let Some(distance) = distances[valve][*next] {
+                    let remaining = time_left - distance - 1;
+                    if remaining > 0 {
+                        pending.push((*next, remaining, opened | 1 << bit, pressure + remaining * self.flow_rates[*next]));
+                    }
+                }
+            }
+        }
+        // Opening only some of a subset's valves is allowed
+        for bit in 0..working.len() {
+            for subset in 0..best.len() {
+                if subset & 1 << bit != 0 {
+                    best[subset] = best[subset].max(best[subset ^ 1 << bit]);
+                }
+            }
+        }
+        Ok(best)
+    }
+
+    /// The most pressure `agents` agents, all starting at `start` and moving at the same time,
+    /// can release in `time` minutes, assuming they each open different valves
+    pub fn best_pressure(&self, start: &str, time: i64, agents: usize) -> Result<i64, ValveError> {
+        let best = self.best_by_subset(start, time)?;
+        let everything = best.len() - 1;
+        // The best pressure for each subset, shared between the agents considered so far
+        let mut combined = vec![0; best.len()];
+        for agent in 1..=agents {
+            let share = |subset: usize| {
+                let mut most = 0;
+                let mut part = subset;
+                loop {
+                    most = most.max(best[part] + combined[subset ^ part]);
+                    if part == 0 {
+                        return most;
+                    }
+                    part = (part - 1) & subset;
+                }
+            };
+            if agent == agents {
+                return Ok(share(everything));
+            }
+            combined = (0..best.len()).map(share).collect();
+        }
+        Ok(0)
+    }
+}
@@ -0,0 +1,194 @@
+use std::{collections::{BTreeMap, HashSet}, ops::{Index, IndexMut}, hash::{Hash, Hasher}};
+
+/// What the machine should do after an instruction has executed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Control {
+    /// Continue with the following instruction
+    Next,
+    /// Move the program counter by the given offset, relative to the current instruction
+    Jump(isize),
+    /// Move the program counter to the given instruction
+    JumpTo(usize),
+    /// Stop the machine
+    Halt
+}
+
+/// Why `Machine::run()` stopped
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The program counter left the program, or an instruction returned `Control::Halt`
+    Halted,
+    /// The instruction at this address is a breakpoint, and hasn't been executed yet
+    Breakpoint(usize),
+    /// The instruction at this address was about to run with exactly the same registers as
+    /// a previous time, so the program would never terminate
+    Loop(usize)
+}
+
+/// A set of named integer registers. Registers which haven't been set read as 0, and compare
+/// equal to registers which have been explicitly set to 0.
+#[derive(Debug, Clone, Default)]
+pub struct Registers {
+    pub values: BTreeMap<String, i64>
+}
+
+impl Registers {
+    pub fn get(&self, name: &str) -> i64 {
+        self.values.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, name: &str, value: i64) {
+        self.values.insert(name.to_string(), value);
+    }
+}
+
+impl Registers {
+    fn non_zero(&self) -> impl Iterator<Item = (&String, &i64)> {
+        self.values.iter().filter(|(_, v)| **v != 0)
+    }
+}
+
+impl PartialEq for Registers {
+    fn eq(&self, other: &Self) -> bool {
+        self.non_zero().eq(other.non_zero())
+    }
+}
+
+impl Eq for Registers {}
+
+impl Hash for Registers {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for entry in self.non_zero() {
+            entry.hash(state);
+        }
+    }
+}
+
+impl Index<&str> for Registers {
+    type Output = i64;
+    fn index(&self, name: &str) -> &i64 {
+        self.values.get(name).unwrap_or(&0)
+    }
+}
+
+impl IndexMut<&str> for Registers {
+    fn index_mut(&mut self, name: &str) -> &mut i64 {
+        self.values.entry(name.to_string()).or_insert(0)
+    }
+}
+
+/// An instruction set for a `Machine`, usually implemented by an enum with one variant per opcode.
+pub trait InstructionSet {
+    /// The number of clock cycles the instruction takes to complete
+    fn cycles(&self) -> usize {
+        1
+    }
+
+    /// Applies the instruction's effects. This happens at the end of its final cycle, so hooks
+    /// called during the instruction's cycles see the registers as they were beforehand.
+    fn execute(&self, registers: &mut Registers) -> Control;
+}
+
+/// A simple register machine which runs a program written in a user-defined `InstructionSet`,
+/// keeping track of the clock cycle.
+///
+/// Hooks passed to `step()` and `run()` are called during every clock cycle with the cycle
+/// number (starting at 1) and the registers, which makes it easy to sample the machine's
+/// state at particular cycles, or to drive a display from it.
+///
+/// Example usage:
+/// ```
+/// # use aochelpers::{Control, InstructionSet, Machine, Outcome, Registers};
+/// enum Op { Inc(&'static str), Jnz(&'static str, isize) }
+/// impl InstructionSet for Op {
+///     fn execute(&self, registers: &mut Registers) -> Control {
+///         match self {
+///             Op::Inc(r) => { registers[r] += 1; Control::Next }
+///             Op::Jnz(r, _) if registers[r] == 0 => Control::Next,
+///             Op::Jnz(_, offset) => Control::Jump(*offset)
+///         }
+///     }
+/// }
+/// let mut machine = Machine::new(vec![Op::Inc("a"), Op::Jnz("a", 0)]);
+/// machine.detect_loops = true;
+/// assert_eq!(machine.run(|_, _| {}), Outcome::Loop(1));
+/// assert_eq!(machine.registers["a"], 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Machine<I> {
+    pub program: Vec<I>,
+    pub registers: Registers,
+    /// Address of the next instruction to execute
+    pub pc: usize,
+    /// Number of clock cycles completed so far
+    pub cycle: usize,
+    /// Addresses at which `run()` should pause
+    pub breakpoints: HashSet<usize>,
+    /// Whether `run()` should stop if the machine returns to a state it has already been in.
+    /// Off by default, as it means remembering every state.
+    pub detect_loops: bool,
+    halted: bool,
+    paused_at: Option<usize>,
+    seen: HashSet<(usize, Registers)>
+}
+
+impl<I: InstructionSet> Machine<I> {
+    pub fn new(program: Vec<I>) -> Self {
+        Machine {
+            program,
+            registers: Registers::default(),
+            pc: 0,
+            cycle: 0,
+            breakpoints: HashSet::new(),
+            detect_loops: false,
+            halted: false,
+            paused_at: None,
+            seen: HashSet::new()
+        }
+    }
+
+    /// Has the machine stopped, either by running off the end of the program or by executing `Control::Halt`?
+    pub fn halted(&self) -> bool {
+        self.halted || self.pc >= self.program.len()
+    }
+
+    /// Executes a single instruction, calling `hook` during each of its cycles.
+    /// Returns `false` without doing anything if the machine has halted.
+    pub fn step(&mut self, mut hook: impl FnMut(usize, &Registers)) -> bool {
+        if self.halted() {
+            return false;
+        }
+        self.paused_at = None;
+        let instruction = &self.program[self.pc];
+        for _ in 0..instruction.cycles() {
+            self.cycle += 1;
+            hook(self.cycle, &self.registers);
+        }
+        match instruction.execute(&mut self.registers) {
+            Control::Next => self.pc += 1,
+            Control::JumpTo(address) => self.pc = address,
+            Control::Jump(offset) => match self.pc.checked_add_signed(offset) {
+                Some(address) => self.pc = address,
+                None => self.halted = true
+            },
+            Control::Halt => self.halted = true
+        }
+        true
+    }
+
+    /// Runs until the machine halts, reaches a breakpoint or (if `detect_loops` is set) is found
+    /// to be in an infinite loop. Running again after stopping at a breakpoint resumes from it.
+    pub fn run(&mut self, mut hook: impl FnMut(usize, &Registers)) -> Outcome {
+        while !self.halted() {
+            if self.breakpoints.contains(&self.pc) && self.paused_at != Some(self.pc) {
+                self.paused_at = Some(self.pc);
+                return Outcome::Breakpoint(self.pc);
+            }
+            if self.detect_loops && !self.seen.insert((self.pc, self.registers.clone())) {
+                return Outcome::Loop(self.pc);
+            }
+            self.step(&mut hook);
+        }
+        Outcome::Halted
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{cmp::Ordering, collections::{HashMap, HashSet}};
 use aochelpers::*;
 
 #[test]
@@ -350,4 +350,1476 @@ fn test_label_ends() {
     let value: Label = res.unwrap();
     assert!(value.ends_with('z'));
     assert!(!value.ends_with('a'))
-}
\ No newline at end of file
+}
+#[test]
+fn hex_distance() {
+    let origin = HexCoordinate{q:0, r:0};
+    assert_eq!(origin.distance(&origin), 0);
+    assert_eq!(origin.distance(&HexCoordinate{q:1, r:-1}), 1);
+    assert_eq!(origin.distance(&HexCoordinate{q:3, r:-1}), 3);
+    assert_eq!(HexCoordinate{q:-2, r:3}.distance(&HexCoordinate{q:2, r:-1}), 4);
+}
+
+#[test]
+fn hex_cube_roundtrip() {
+    let hex = HexCoordinate{q:2, r:-3};
+    assert_eq!(hex.cube(), (2, -3, 1));
+    assert_eq!(HexCoordinate::from_cube(2, -3, 1), Some(hex));
+    assert_eq!(HexCoordinate::from_cube(2, -3, 2), None);
+}
+
+#[test]
+fn hex_doubled_conversion() {
+    let centre = Coordinate{x:1, y:1};
+    let hex = HexCoordinate::from_doubled(centre);
+    assert_eq!(hex.to_doubled(), centre);
+    let mut expected = centre.hex_neighbours();
+    let mut converted = hex.neighbours().iter().map(|h| h.to_doubled()).collect::<Vec<_>>();
+    expected.sort();
+    converted.sort();
+    assert_eq!(converted, expected);
+}
+
+#[test]
+fn hex_direction_rotation() {
+    assert_eq!(HexDirection::East.rotate_anticlockwise(), HexDirection::NorthEast);
+    assert_eq!(HexDirection::East.rotate_clockwise(), HexDirection::SouthEast);
+    assert_eq!(HexDirection::NorthWest.opposite(), HexDirection::SouthEast);
+    assert_eq!(HexDirection::West.rotate(-7), HexDirection::NorthWest);
+}
+
+#[test]
+fn hex_coordinate_rotation() {
+    let east = HexCoordinate{q:1, r:0};
+    assert_eq!(east.rotate(1), HexCoordinate::unit(HexDirection::NorthEast));
+    assert_eq!(east.rotate(-1), HexCoordinate::unit(HexDirection::SouthEast));
+    assert_eq!(east.rotate(6), east);
+    let centre = HexCoordinate{q:5, r:5};
+    assert_eq!((centre + east).rotate_around(&centre, 3), centre + HexCoordinate::unit(HexDirection::West));
+}
+
+#[test]
+fn hex_rings_and_spirals() {
+    let origin = HexCoordinate{q:0, r:0};
+    assert_eq!(origin.ring(0), vec![origin]);
+    let ring = origin.ring(2);
+    assert_eq!(ring.len(), 12);
+    assert!(ring.iter().all(|h| h.distance(&origin) == 2));
+    assert_eq!(ring.iter().collect::<HashSet<_>>().len(), 12);
+    let spiral = origin.spiral(2);
+    assert_eq!(spiral.len(), 19);
+    assert_eq!(spiral[0], origin);
+}
+
+#[test]
+fn hex_step_parsing() {
+    assert_eq!(parse_hex_steps("esenee").unwrap(), 
+        vec![HexDirection::East, HexDirection::SouthEast, HexDirection::NorthEast, HexDirection::East]);
+    assert_eq!(parse_hex_steps("ne,ne,sw").unwrap(), 
+        vec![HexDirection::NorthEast, HexDirection::NorthEast, HexDirection::SouthWest]);
+    assert!(parse_hex_steps("nex").is_err());
+    assert!(parse_hex_steps("n").is_err());
+    let origin = HexCoordinate{q:0, r:0};
+    assert_eq!(origin.walk(&parse_hex_steps("nwwswee").unwrap()), origin);
+    assert_eq!("sw".parse::<HexDirection>().unwrap().to_string(), "sw");
+}
+
+#[test]
+fn coordinate_n_arithmetic() {
+    let mut base = CoordinateN::new([1, 1, 1, 1]);
+    let delta = CoordinateN::new([2, 3, -1, 0]);
+    assert_eq!(base + delta, CoordinateN::new([3, 4, 0, 1]));
+    assert_eq!(base - delta, CoordinateN::new([-1, -2, 2, 1]));
+    base += delta;
+    assert_eq!(base, CoordinateN::new([3, 4, 0, 1]));
+    base -= delta;
+    base[3] = 7;
+    assert_eq!(base.to_string(), "(1,1,1,7)");
+}
+
+#[test]
+fn coordinate_n_distances() {
+    let origin = CoordinateN::<i32, 4>::origin();
+    let other = CoordinateN::new([1, -2, 3, -4]);
+    assert_eq!(origin.manhattan_distance(&other), 10);
+    assert_eq!(other.manhattan_distance(&origin), 10);
+    assert_eq!(origin.chebyshev_distance(&other), 4);
+}
+
+#[test]
+fn coordinate_n_neighbours() {
+    let origin = CoordinateN::<i32, 4>::origin();
+    let neighbours = origin.neighbours().collect::<HashSet<_>>();
+    assert_eq!(neighbours.len(), 8);
+    assert!(neighbours.iter().all(|n| n.manhattan_distance(&origin) == 1));
+    let extended = origin.extended_neighbours().collect::<HashSet<_>>();
+    assert_eq!(extended.len(), 80);
+    assert!(!extended.contains(&origin));
+    assert!(extended.iter().all(|n| n.chebyshev_distance(&origin) == 1));
+}
+
+#[test]
+fn coordinate_n_matches_fixed_dimensions() {
+    let point = Coordinate3d{x:1, y:1, z:1};
+    let mut expected = point.extended_neighbours();
+    let mut generated = CoordinateN::from(point).extended_neighbours().map(Coordinate3d::from).collect::<Vec<_>>();
+    expected.sort();
+    generated.sort();
+    assert_eq!(generated, expected);
+
+    let flat = Coordinate{x:3, y:-2};
+    let roundtrip: Coordinate<i32> = CoordinateN::from(flat).into();
+    assert_eq!(roundtrip, flat);
+    assert_eq!(CoordinateN::from(flat).neighbours().count(), flat.neighbours().len());
+}
+
+#[test]
+fn neighbour_arrays_match_vecs() {
+    let point = Coordinate{x:1, y:1};
+    assert_eq!(point.neighbours_array().to_vec(), point.neighbours());
+    assert_eq!(point.extended_neighbours_array().len(), 8);
+    assert_eq!(point.hex_neighbours_array().len(), 6);
+    let point3d = Coordinate3d{x:1, y:1, z:1};
+    assert_eq!(point3d.neighbours_array().to_vec(), point3d.neighbours());
+    assert_eq!(point3d.extended_neighbours_array().len(), 26);
+}
+
+#[test]
+fn neighbours_within_bounds() {
+    let bounds = Rectangle::new(Coordinate{x:0, y:0}, Coordinate{x:2, y:2});
+    assert_eq!(Coordinate{x:0, y:0}.neighbours_within(&bounds).collect::<Vec<_>>(), 
+        vec![Coordinate{x:1, y:0}, Coordinate{x:0, y:1}]);
+    assert_eq!(Coordinate{x:0, y:0}.extended_neighbours_within(&bounds).count(), 3);
+    assert_eq!(Coordinate{x:1, y:1}.extended_neighbours_within(&bounds).count(), 8);
+
+    let cuboid = Cuboid::new(Coordinate3d{x:0, y:0, z:0}, Coordinate3d{x:2, y:2, z:2});
+    assert_eq!(Coordinate3d{x:0, y:0, z:0}.neighbours_within(&cuboid).count(), 3);
+    assert_eq!(Coordinate3d{x:0, y:0, z:0}.extended_neighbours_within(&cuboid).count(), 7);
+}
+
+#[test]
+fn checked_neighbours_unsigned() {
+    let corner = Coordinate::<usize>{x:0, y:0};
+    assert_eq!(corner.checked_neighbours().collect::<Vec<_>>(), 
+        vec![Coordinate{x:1, y:0}, Coordinate{x:0, y:1}]);
+    assert_eq!(corner.checked_extended_neighbours().collect::<Vec<_>>(), 
+        vec![Coordinate{x:0, y:1}, Coordinate{x:1, y:0}, Coordinate{x:1, y:1}]);
+    assert_eq!(Coordinate::<usize>{x:1, y:1}.checked_extended_neighbours().collect::<Vec<_>>(),
+        Coordinate::<usize>{x:1, y:1}.extended_neighbours());
+    assert_eq!(Coordinate::<u8>{x:255, y:3}.checked_neighbours().count(), 3);
+    // Hex neighbours reach two columns to the side, so even x = 1 loses one
+    assert_eq!(Coordinate::<usize>{x:1, y:0}.checked_hex_neighbours().collect::<Vec<_>>(),
+        vec![Coordinate{x:3, y:0}, Coordinate{x:2, y:1}, Coordinate{x:0, y:1}]);
+    assert_eq!(Coordinate::<usize>{x:2, y:1}.checked_hex_neighbours().collect::<Vec<_>>(),
+        Coordinate::<usize>{x:2, y:1}.hex_neighbours());
+}
+
+#[test]
+fn neighbours_within_unsigned_edges() {
+    let bounds = Rectangle::new(Coordinate::<usize>{x:0, y:0}, Coordinate{x:2, y:2});
+    assert_eq!(Coordinate::<usize>{x:0, y:0}.neighbours_within(&bounds).collect::<Vec<_>>(),
+        vec![Coordinate{x:1, y:0}, Coordinate{x:0, y:1}]);
+    assert_eq!(Coordinate::<usize>{x:0, y:2}.extended_neighbours_within(&bounds).count(), 3);
+
+    let cuboid = Cuboid::new(Coordinate3d::<usize>{x:0, y:0, z:0}, Coordinate3d{x:2, y:2, z:2});
+    let corner = Coordinate3d::<usize>{x:0, y:0, z:0};
+    assert_eq!(corner.checked_neighbours().count(), 3);
+    assert_eq!(corner.checked_extended_neighbours().count(), 7);
+    assert_eq!(corner.neighbours_within(&cuboid).count(), 3);
+    assert_eq!(corner.extended_neighbours_within(&cuboid).count(), 7);
+    let middle = Coordinate3d::<usize>{x:1, y:1, z:1};
+    assert_eq!(middle.checked_extended_neighbours().collect::<Vec<_>>(), middle.extended_neighbours());
+    assert_eq!(Coordinate3d::<u8>{x:255, y:1, z:1}.checked_neighbours().count(), 5);
+}
+
+#[test]
+fn chebyshev_and_euclidean_distance() {
+    assert_eq!(Coordinate{x:0, y:0}.chebyshev_distance(&Coordinate{x:-3, y:2}), 3);
+    assert_eq!(Coordinate{x:1, y:1}.chebyshev_distance(&Coordinate{x:2, y:2}), 1);
+    assert_eq!(Coordinate{x:0, y:0}.squared_euclidean_distance(&Coordinate{x:-3, y:4}), 25);
+    assert_eq!(Coordinate::<usize>{x:5, y:1}.squared_euclidean_distance(&Coordinate{x:2, y:5}), 25);
+}
+
+#[test]
+fn step_towards() {
+    let tail = Coordinate{x:0, y:0};
+    assert_eq!(tail.step_towards(&Coordinate{x:2, y:1}), Coordinate{x:1, y:1});
+    assert_eq!(tail.step_towards(&Coordinate{x:0, y:-5}), Coordinate{x:0, y:-1});
+    assert_eq!(tail.step_towards(&tail), tail);
+}
+
+#[test]
+fn manhattan_ring_and_disk() {
+    let centre = Coordinate{x:2, y:-1};
+    assert_eq!(centre.manhattan_ring(0).collect::<Vec<_>>(), vec![centre]);
+    let ring = centre.manhattan_ring(3).collect::<HashSet<_>>();
+    assert_eq!(ring.len(), 12);
+    assert!(ring.iter().all(|c| c.manhattan_distance(&centre) == 3));
+    let disk = centre.manhattan_disk(3).collect::<Vec<_>>();
+    assert_eq!(disk.len(), 25);
+    assert!(disk.iter().all(|c| c.manhattan_distance(&centre) <= 3));
+    assert!(disk.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn bresenham_lines() {
+    let start = Coordinate{x:498, y:4};
+    assert_eq!(start.line_to(&Coordinate{x:498, y:6}).collect::<Vec<_>>(),
+        vec![Coordinate{x:498, y:4}, Coordinate{x:498, y:5}, Coordinate{x:498, y:6}]);
+    assert_eq!(start.line_to(&Coordinate{x:496, y:4}).count(), 3);
+    assert_eq!(start.line_to(&start).collect::<Vec<_>>(), vec![start]);
+    assert_eq!(Coordinate{x:0, y:0}.line_to(&Coordinate{x:3, y:-3}).last(), Some(Coordinate{x:3, y:-3}));
+    assert_eq!(Coordinate{x:0, y:0}.line_to(&Coordinate{x:4, y:2}).collect::<Vec<_>>(),
+        vec![Coordinate{x:0, y:0}, Coordinate{x:1, y:1}, Coordinate{x:2, y:1}, Coordinate{x:3, y:2}, Coordinate{x:4, y:2}]);
+}
+
+#[test]
+fn rotation_and_reflection() {
+    let east = Coordinate{x:1, y:0};
+    assert_eq!(east.rotate_clockwise(), Coordinate{x:0, y:1});
+    assert_eq!(east.rotate_anticlockwise(), Coordinate{x:0, y:-1});
+    assert_eq!(east.rotate_clockwise().rotate_anticlockwise(), east);
+    let pivot = Coordinate{x:5, y:5};
+    assert_eq!(Coordinate{x:6, y:5}.rotate_clockwise_around(&pivot), Coordinate{x:5, y:6});
+    assert_eq!(Coordinate{x:6, y:5}.rotate_anticlockwise_around(&pivot), Coordinate{x:5, y:4});
+    assert_eq!(Coordinate{x:1, y:2}.reflect_x(3), Coordinate{x:5, y:2});
+    assert_eq!(Coordinate{x:1, y:2}.reflect_y(0), Coordinate{x:1, y:-2});
+}
+
+#[test]
+fn polygon_square() {
+    let square = Polygon::new(vec![Coordinate{x:0, y:0}, Coordinate{x:2, y:0}, Coordinate{x:2, y:2}, Coordinate{x:0, y:2}]);
+    assert_eq!(square.doubled_area(), 8);
+    assert_eq!(square.area(), 4);
+    assert_eq!(square.boundary_points(), 8);
+    assert_eq!(square.interior_points(), 1);
+    assert_eq!(square.enclosed_points(), 9);
+}
+
+#[test]
+fn polygon_closed_loop_and_triangle() {
+    let triangle = Polygon::new(vec![Coordinate{x:0, y:0}, Coordinate{x:4, y:0}, Coordinate{x:0, y:3}, Coordinate{x:0, y:0}]);
+    assert_eq!(triangle.vertices.len(), 3);
+    assert_eq!(triangle.doubled_area(), 12);
+    assert_eq!(triangle.boundary_points(), 8);
+    assert_eq!(triangle.interior_points(), 3);
+}
+
+#[test]
+fn polygon_without_area() {
+    let empty = Polygon::<i32>::new(vec![]);
+    assert_eq!((empty.doubled_area(), empty.interior_points()), (0, 0));
+    let point = Polygon::new(vec![Coordinate{x:3, y:4}]);
+    assert_eq!(point.interior_points(), 0);
+    let line = Polygon::new(vec![Coordinate{x:0, y:0}, Coordinate{x:6, y:0}]);
+    assert_eq!(line.interior_points(), 0);
+    let collinear = Polygon::new(vec![Coordinate{x:0, y:0}, Coordinate{x:2, y:2}, Coordinate{x:5, y:5}]);
+    assert_eq!(collinear.interior_points(), 0);
+}
+
+#[test]
+fn polygon_from_steps() {
+    // Dig plan from a "lagoon" puzzle: a loop of trenches enclosing 62 squares
+    let steps = [
+        (Direction::East, 6), (Direction::South, 5), (Direction::West, 2), (Direction::South, 2),
+        (Direction::East, 2), (Direction::South, 2), (Direction::West, 5), (Direction::North, 2),
+        (Direction::West, 1), (Direction::North, 2), (Direction::East, 2), (Direction::North, 3),
+        (Direction::West, 2), (Direction::North, 2)];
+    let lagoon = Polygon::from_steps(Coordinate{x:0_i64, y:0}, steps);
+    assert_eq!(lagoon.boundary_points(), 38);
+    assert_eq!(lagoon.enclosed_points(), 62);
+}
+
+#[test]
+fn polygon_contains() {
+    let shape = Polygon::new(vec![Coordinate{x:0, y:0}, Coordinate{x:4, y:0}, Coordinate{x:4, y:4}, 
+                                  Coordinate{x:2, y:2}, Coordinate{x:0, y:4}]);
+    assert!(shape.contains(&Coordinate{x:1, y:1}));
+    assert!(shape.contains(&Coordinate{x:4, y:2}));
+    assert!(shape.on_boundary(&Coordinate{x:3, y:3}));
+    assert!(!shape.on_boundary(&Coordinate{x:3, y:2}));
+    assert!(!shape.contains(&Coordinate{x:2, y:3}));
+    assert!(!shape.contains(&Coordinate{x:5, y:1}));
+    assert!(!shape.contains(&Coordinate{x:-1, y:0}));
+}
+
+const OCR_SMALL: &str = "
+.##..###..####.#..#.
+#..#.#..#.#....#.#..
+#..#.###..###..##...
+####.#..#.#....#.#..
+#..#.#..#.#....#.#..
+#..#.###..####.#..#.
+";
+
+#[test]
+fn ocr_small_font() {
+    assert_eq!(ocr(OCR_SMALL, '#', '.'), Ok("ABEK".to_string()));
+    let crt = OCR_SMALL.replace('#', "█").replace('.', " ");
+    assert_eq!(ocr(&crt, '█', ' '), Ok("ABEK".to_string()));
+}
+
+#[test]
+fn ocr_large_font() {
+    let image = "#....#..######
+##...#.......#
+##...#.......#
+#.#..#......#.
+#.#..#.....#..
+#..#.#....#...
+#..#.#...#....
+#...##..#.....
+#...##..#.....
+#....#..######";
+    assert_eq!(ocr(image, '#', '.'), Ok("NZ".to_string()));
+}
+
+#[test]
+fn ocr_from_points_and_grid() {
+    let points = OCR_SMALL.lines().filter(|l| !l.is_empty()).enumerate()
+        .flat_map(|(y, line)| line.chars().enumerate().filter(|(_, c)| *c == '#')
+            .map(move |(x, _)| Coordinate{x: x as i32 - 10, y: y as i32 + 3}))
+        .collect::<HashSet<_>>();
+    assert_eq!(ocr_points(&points, &Coordinate{x: -10, y: 0}), Ok("ABEK".to_string()));
+    let grid = parse_number_grid::<i32, u8>("1111\n1000\n1110\n1000\n1000\n1000")
+        .into_iter().map(|(c, v)| (c, v == 1)).collect::<HashMap<_, _>>();
+    assert_eq!(ocr_grid(&grid), Ok("F".to_string()));
+}
+
+#[test]
+fn ocr_errors() {
+    let broken = OCR_SMALL.replacen(".##.", "####", 1);
+    assert_eq!(ocr(&broken, '#', '.'), Err(OcrError::UnrecognisedGlyphs { columns: vec![0], partial: "?BEK".to_string() }));
+    assert_eq!(ocr("#\n#", '#', '.'), Err(OcrError::UnknownFont { height: 2 }));
+    assert_eq!(ocr(OCR_SMALL, '#', ' '), Err(OcrError::UnexpectedPixel { line: 2, column: 1, found: '.' }));
+    // A lit pixel in the gap between letters doesn't match any letter
+    let smudged = OCR_SMALL.replacen(".##..", ".##.#", 1);
+    assert_eq!(ocr(&smudged, '#', '.'), Err(OcrError::UnrecognisedGlyphs { columns: vec![0], partial: "?BEK".to_string() }));
+}
+
+#[test]
+fn ocr_leading_blank_columns() {
+    let image = ".###.#...#\n..#..#...#\n..#...#.#.\n..#....#..\n..#....#..\n.###...#..";
+    assert_eq!(ocr(image, '#', '.'), Ok("IY".to_string()));
+    let lone_i = ".###\n..#.\n..#.\n..#.\n..#.\n.###";
+    let points = lone_i.lines().enumerate()
+        .flat_map(|(y, line)| line.chars().enumerate().filter(|(_, c)| *c == '#').map(move |(x, _)| Coordinate{x: x as i32, y: y as i32}))
+        .collect::<Vec<_>>();
+    assert_eq!(ocr_points(&points, &Coordinate{x: 0, y: 0}), Ok("I".to_string()));
+    let grid = lone_i.lines().enumerate()
+        .flat_map(|(y, line)| line.chars().enumerate().map(move |(x, c)| (Coordinate{x: x as i32 + 5, y: y as i32}, c == '#')))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(ocr_grid(&grid), Ok("I".to_string()));
+    let large_j = "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n#...#.\n.###..";
+    assert_eq!(ocr(large_j, '#', '.'), Ok("J".to_string()));
+}
+
+#[test]
+fn bounding_box() {
+    let points = [Coordinate{x:3, y:-1}, Coordinate{x:-2, y:4}, Coordinate{x:0, y:0}];
+    assert_eq!(Rectangle::bounding_box(&points), Some(Rectangle::new(Coordinate{x:-2, y:-1}, Coordinate{x:3, y:4})));
+    assert_eq!(Rectangle::<i32>::bounding_box(&[]), None);
+}
+
+#[test]
+fn render_points() {
+    let points = HashSet::from([Coordinate{x:1, y:1}, Coordinate{x:3, y:2}]);
+    let mut frame = Frame::from_points(&points, '#');
+    assert_eq!(frame.to_string(), "#..\n..#");
+    assert_eq!((frame.width(), frame.height()), (3, 2));
+    frame.overlay(&[Coordinate{x:2, y:1}, Coordinate{x:9, y:9}], '@');
+    assert_eq!(frame.to_string(), "#@.\n..#");
+    assert_eq!(frame.get(&Coordinate{x:3, y:2}), Some('#'));
+    assert_eq!(frame.get(&Coordinate{x:0, y:0}), None);
+    assert_eq!(Frame::<i32>::from_points(&[], '#').to_string(), "");
+}
+
+#[test]
+fn render_map_and_labels() {
+    let grid = HashMap::from([(Coordinate{x:9, y:-1}, 1), (Coordinate{x:10, y:0}, 2)]);
+    let frame = Frame::from_map(&grid, |v| char::from_digit(*v, 10).unwrap());
+    assert_eq!(frame.to_string(), "1.\n.2");
+    assert_eq!(frame.labelled(), "    1\n   90\n-1 1.\n 0 .2");
+}
+
+#[test]
+fn render_images() {
+    let frame = Frame::new(Rectangle::new(Coordinate{x:0, y:0}, Coordinate{x:1, y:0}), |c| if c.x == 0 {'#'} else {'.'});
+    let (width, height, pixels) = frame.to_rgb(2, default_palette);
+    assert_eq!((width, height), (4, 2));
+    assert_eq!(pixels.len(), 4 * 2 * 3);
+    assert_eq!(&pixels[0..6], &[255, 255, 255, 255, 255, 255]);
+    assert_eq!(&pixels[6..12], &[0, 0, 0, 0, 0, 0]);
+
+    let directory = std::env::temp_dir();
+    let ppm = directory.join("aochelpers_render_test.ppm");
+    frame.write_ppm(&ppm, default_palette).unwrap();
+    let contents = std::fs::read(&ppm).unwrap();
+    assert!(contents.starts_with(b"P6\n2 1\n255\n"));
+    assert_eq!(contents.len(), 11 + 6);
+    let png = directory.join("aochelpers_render_test.png");
+    frame.write_png(&png, 3, default_palette).unwrap();
+    assert!(std::fs::read(&png).unwrap().starts_with(&[0x89, b'P', b'N', b'G']));
+    std::fs::remove_file(ppm).unwrap();
+    std::fs::remove_file(png).unwrap();
+}
+
+#[test]
+fn recorder_deduplicates_frames() {
+    let mut recorder = Recorder::new(std::time::Duration::ZERO);
+    assert!(recorder.is_empty());
+    assert!(recorder.record_points(&[Coordinate{x:0, y:0}], '#'));
+    assert!(!recorder.record_points(&[Coordinate{x:0, y:0}], '#'));
+    assert!(recorder.record_points(&[Coordinate{x:1, y:2}, Coordinate{x:2, y:2}], '#'));
+    assert_eq!(recorder.len(), 2);
+    assert_eq!(recorder.bounds(), Some(Rectangle::new(Coordinate{x:0, y:0}, Coordinate{x:2, y:2})));
+}
+
+#[test]
+fn recorder_playback_and_gif() {
+    let mut recorder = Recorder::new(std::time::Duration::ZERO);
+    recorder.record_points(&[Coordinate{x:0, y:0}], '#');
+    recorder.record_map(&HashMap::from([(Coordinate{x:1, y:1}, 'E')]), |c| *c);
+    let mut output = Vec::new();
+    recorder.play(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output, "\x1b[2J\x1b[H#.\n..\n\x1b[2J\x1b[H..\n.E\n");
+
+    let path = std::env::temp_dir().join("aochelpers_recorder_test.gif");
+    recorder.write_gif(&path, 2, default_palette).unwrap();
+    assert!(std::fs::read(&path).unwrap().starts_with(b"GIF89a"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn normalise_input_line_endings() {
+    assert_eq!(normalise_input("a  \r\nb\r\n\r\n\r\n"), "a\nb");
+    assert_eq!(normalise_input("  indented\n"), "  indented");
+    assert_eq!(normalise_input("\n\n"), "");
+}
+
+#[test]
+fn split_sections_blank_lines() {
+    assert_eq!(split_sections("1\n2\n\n3\n  "), vec!["1\n2", "3"]);
+    assert_eq!(split_sections("1\r\n\r\n2\r\n"), vec!["1", "2"]);
+    assert_eq!(split_sections("\n1\n\n  \n\n2"), vec!["1", "2"]);
+    assert!(split_sections("").is_empty());
+}
+
+#[test]
+fn typed_sections() {
+    let (header, lines) = sections::<(String, Vec<i32>)>("header\r\n\r\n1\r\n-2\r\n").unwrap();
+    assert_eq!(header, "header");
+    assert_eq!(lines, vec![1, -2]);
+    let groups = sections::<Vec<Vec<u32>>>("1\n2\n\n3\n").unwrap();
+    assert_eq!(groups, vec![vec![1, 2], vec![3]]);
+}
+
+#[test]
+fn typed_section_errors() {
+    let error = sections::<(String, Vec<i32>)>("header\n\n1\nx").unwrap_err();
+    assert_eq!(error.to_string(), "Couldn't parse section 2");
+    assert!(format!("{:#}", error).contains("line 2"));
+    assert!(sections::<(String, String)>("only one").is_err());
+}
+
+#[test]
+fn ints_extraction() {
+    assert_eq!(ints::<i64>("Sensor at x=2, y=-18: closest beacon is at x=-2, y=15"), vec![2, -18, -2, 15]);
+    assert_eq!(ints::<u32>("2-4,6-8"), vec![2, 4, 6, 8]);
+    assert_eq!(ints::<i32>("Blueprint 1: costs 4 ore -- and -3"), vec![1, 4, -3]);
+    assert_eq!(ints::<u8>("300 and 12"), vec![12]);
+    assert!(ints::<i32>("no numbers - here").is_empty());
+}
+
+#[test]
+fn scan_templates() {
+    let fields: (usize, usize, usize) = scan!("move {} from {} to {}", "move 13 from 1 to 2").unwrap();
+    assert_eq!(fields, (13, 1, 2));
+    let fields: (usize, usize, usize) = scan!("move {} from {} to {}", "  move   1\tfrom 2 to 3\r").unwrap();
+    assert_eq!(fields, (1, 2, 3));
+    let (x, y) = scan!("x={}, y={}", "x=-2, y=15" => i32, i32).unwrap();
+    assert_eq!((x, y), (-2, 15));
+    let (id,) = scan!("Monkey {}:", "Monkey 7:" => usize).unwrap();
+    assert_eq!(id, 7);
+    let (operator, operand) = scan!("new = old {} {}", "new = old * old" => char, String).unwrap();
+    assert_eq!((operator, operand.as_str()), ('*', "old"));
+    let values: Vec<u8> = scan!("{}-{},{}-{}", "2-4,6-8").unwrap();
+    assert_eq!(values, vec![2, 4, 6, 8]);
+}
+
+#[test]
+fn scan_errors() {
+    let error = scan!("move {} from {} to {}", "move 1 form 2 to 3" => usize, usize, usize).unwrap_err();
+    assert!(matches!(error, ScanError::Mismatch { column: 6, .. }));
+    let error = scan!("move {} from {} to {}", "move x from 2 to 3" => usize, usize, usize).unwrap_err();
+    assert!(matches!(error, ScanError::InvalidField { index: 1, .. }));
+    assert!(error.to_string().contains("\"x\""));
+    let error = scan!("move {} from {}", "move 1 from 2" => usize, usize, usize).unwrap_err();
+    assert_eq!(error, ScanError::FieldCount { template: 2, requested: 3 });
+    assert!(scan!("Monkey {}:", "Monkey 7: extra" => String).is_err());
+    assert!(scan!("a {} b", "a  b" => String).is_err());
+}
+
+#[test]
+fn parse_lines_reports_location() {
+    let error = parse_lines("1,2\n3,x\n5,6", |l| scan!("{},{}", l => i32, i32)).unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (2, 3, "x"));
+    assert!(error.to_string().starts_with("line 2, column 3: expected field 2"));
+    let error = parse_lines("  10\n  -x", |l| l.trim().parse::<i64>()).unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (2, 3, "-x"));
+    let error = parse_lines("move 1 to 2", |l| scan!("move {} from {}", l => u8, u8)).unwrap_err();
+    assert_eq!((error.line, error.column), (1, 6));
+}
+
+#[test]
+fn parse_lines_all_errors() {
+    let errors = parse_lines_all("1\na\n3\nb", |l| l.parse::<u8>()).unwrap_err();
+    assert_eq!(errors.iter().map(|e| e.line).collect::<Vec<_>>(), vec![2, 4]);
+    assert_eq!(parse_lines_all("1\n2", |l| l.parse::<u8>()), Ok(vec![1, 2]));
+    let custom = parse_lines("ok\nbad", |l| if l == "ok" { Ok(()) } else { Err(ParseError::at(l, &l[1..], "ok")) }).unwrap_err();
+    assert_eq!((custom.line, custom.column, custom.snippet.as_str()), (2, 2, "ad"));
+}
+
+#[test]
+fn parse_error_display() {
+    let error: ParseError = "x".parse::<u8>().unwrap_err().into();
+    assert!(error.to_string().starts_with("expected an integer"));
+    assert_eq!(ParseError::new(4, "y", "a digit").on_line(2).to_string(), "line 2, column 4: expected a digit, found \"y\"");
+    let error = parse_lines("[1,2", |l| if l.ends_with(']') { Ok(()) } else { Err(ParseError::at(l, &l[l.len()..], "']'")) }).unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (5, ""));
+}
+
+#[test]
+fn nested_list_parsing() {
+    let list = " [1, [-2,[\"a \\\"b\\\"\"]],[] ] ".parse::<NestedList>().unwrap();
+    assert_eq!(list, NestedList::List(vec![1.into(), vec![(-2).into(), vec!["a \"b\"".into()].into()].into(), vec![].into()]));
+    assert_eq!(list.to_string(), "[1,[-2,[\"a \\\"b\\\"\"]],[]]");
+    assert_eq!(list.to_string().parse::<NestedList>().unwrap(), list);
+    // Printing and parsing escape strings the same way, whether compact or pretty-printed
+    let awkward = NestedList::List(vec![vec!["line\nbreak\ttab\r\\ \"quoted\" \u{7}bell é".into(), 3.into()].into(), "\\n".into()]);
+    assert_eq!(awkward.to_string(), r#"[["line\nbreak\ttab\r\\ \"quoted\" \u{7}bell é",3],"\\n"]"#);
+    assert_eq!(awkward.to_string().parse::<NestedList>().unwrap(), awkward);
+    assert_eq!(format!("{:#}", awkward).parse::<NestedList>().unwrap(), awkward);
+    assert_eq!(r#""\u{1F600}\q""#.parse::<NestedList>().unwrap(), NestedList::Str("\u{1F600}q".to_string()));
+    assert_eq!(list.depth(), 3);
+    assert_eq!("7".parse::<NestedList>().unwrap(), NestedList::Int(7));
+}
+
+#[test]
+fn nested_list_errors() {
+    let error = "[1,2".parse::<NestedList>().unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (5, ""));
+    let error = "[1,,2]".parse::<NestedList>().unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (4, ","));
+    let error = "[1]]".parse::<NestedList>().unwrap_err();
+    assert_eq!(error.expected, "end of line");
+    let error = "[\"open]".parse::<NestedList>().unwrap_err();
+    assert_eq!(error.column, 2);
+    let error = "[99999999999999999999]".parse::<NestedList>().unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (2, "99999999999999999999"));
+    let error = r#"["\u{zz}x"]"#.parse::<NestedList>().unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (3, r"\u{zz}"));
+    assert!(r#"["\u{110000}"]"#.parse::<NestedList>().is_err());
+    assert!(r#"["\u12"]"#.parse::<NestedList>().is_err());
+}
+
+#[test]
+fn nested_list_packet_order() {
+    let parse = |s: &str| s.parse::<NestedList>().unwrap();
+    assert_eq!(parse("[1,1,3,1,1]").packet_cmp(&parse("[1,1,5,1,1]")), Ordering::Less);
+    assert_eq!(parse("[[1],[2,3,4]]").packet_cmp(&parse("[[1],4]")), Ordering::Less);
+    assert_eq!(parse("[9]").packet_cmp(&parse("[[8,7,6]]")), Ordering::Greater);
+    assert_eq!(parse("[[[]]]").packet_cmp(&parse("[[]]")), Ordering::Greater);
+    assert_eq!(parse("[1,\"a\"]").packet_cmp(&parse("[1,[\"a\"]]")), Ordering::Equal);
+    assert_eq!(parse("[2]").packet_cmp(&parse("[\"1\"]")), Ordering::Less);
+}
+
+#[test]
+fn nested_list_pretty_print() {
+    let list = "[1,[2,[3]],[]]".parse::<NestedList>().unwrap();
+    assert_eq!(format!("{:#}", list), "[\n  1,\n  [\n    2,\n    [3]\n  ],\n  []\n]");
+    assert_eq!(format!("{:#}", list).parse::<NestedList>().unwrap(), list);
+}
+
+#[test]
+fn nested_list_snailfish_mutation() {
+    fn explode(number: &mut NestedList) -> bool {
+        let leaves = number.leaf_paths();
+        let Some(i) = leaves.iter().position(|p| p.len() > 4) else {
+            return false;
+        };
+        let pair_path = &leaves[i][..4];
+        let left = number.get(&leaves[i]).and_then(|n| n.as_int()).unwrap();
+        let right = number.get(&leaves[i + 1]).and_then(|n| n.as_int()).unwrap();
+        for (neighbour, value) in [(i.checked_sub(1), left), (Some(i + 2), right)] {
+            if let Some(NestedList::Int(n)) = neighbour.and_then(|n| leaves.get(n)).and_then(|p| number.get_mut(p)) {
+                *n += value;
+            }
+        }
+        *number.get_mut(pair_path).unwrap() = NestedList::Int(0);
+        true
+    }
+    fn split(number: &mut NestedList) -> bool {
+        for path in number.leaf_paths() {
+            let node = number.get_mut(&path).unwrap();
+            if let Some(n) = node.as_int().filter(|n| *n >= 10) {
+                *node = vec![(n / 2).into(), ((n + 1) / 2).into()].into();
+                return true;
+            }
+        }
+        false
+    }
+    let mut number = "[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]".parse::<NestedList>().unwrap();
+    while explode(&mut number) || split(&mut number) {}
+    assert_eq!(number.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+    assert!(number.get(&[0, 5]).is_none());
+    assert!(number.get(&[1, 0, 0]).is_none());
+}
+
+fn rational(numerator: i64, denominator: i64) -> num::BigRational {
+    num::BigRational::new(numerator.into(), denominator.into())
+}
+
+#[test]
+fn expression_evaluation() {
+    let graph = "root: a / b\na: c + c\nb: 4\nc: 3".parse::<ExpressionGraph>().unwrap();
+    assert_eq!(graph.evaluate("root"), Ok(rational(3, 2)));
+    assert_eq!(graph.evaluate("c"), Ok(rational(3, 1)));
+    assert_eq!(graph.operands("root"), Some(("a", "b")));
+    let all = graph.evaluate_all().unwrap();
+    assert_eq!(all.len(), 4);
+    assert_eq!(all["a"], rational(6, 1));
+    let mut graph = graph;
+    graph.insert_value("b", rational(1, 2));
+    graph.insert_operation("c", "b", Operator::Multiply, "b");
+    assert_eq!(graph.evaluate("root"), Ok(rational(1, 1)));
+}
+
+#[test]
+fn expression_errors() {
+    let graph = "a: b + c\nb: 1\nc: d * b\nd: a - b".parse::<ExpressionGraph>().unwrap();
+    assert_eq!(graph.evaluate("a"), Err(ExpressionError::Cycle { path: ["a", "c", "d", "a"].map(String::from).to_vec() }));
+    let graph = "a: b + c\nb: 1".parse::<ExpressionGraph>().unwrap();
+    assert_eq!(graph.evaluate("a"), Err(ExpressionError::Undefined { name: "c".to_string(), referenced_by: Some("a".to_string()) }));
+    assert!(graph.evaluate("z").is_err());
+    let graph = "a: b / c\nb: 1\nc: b - b".parse::<ExpressionGraph>().unwrap();
+    assert_eq!(graph.evaluate("a"), Err(ExpressionError::DivisionByZero { node: "a".to_string() }));
+    let error = "a: b % c".parse::<ExpressionGraph>().unwrap_err();
+    assert_eq!((error.line, error.column), (1, 6));
+    // A second definition of a node is an error rather than quietly replacing the first
+    let error = "a: 1\nb: a + a\n  a: 5".parse::<ExpressionGraph>().unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (3, 3, "a"));
+}
+
+#[test]
+fn expression_solving() {
+    let graph = "lhs: x * two\nrhs: x + five\ntwo: 2\nfive: 5\nx: 100".parse::<ExpressionGraph>().unwrap();
+    assert_eq!(graph.solve("x", "lhs", "rhs"), Ok(rational(5, 1)));
+    let graph = "lhs: ten / x\nten: 10\nrhs: 5".parse::<ExpressionGraph>().unwrap();
+    assert_eq!(graph.solve("x", "lhs", "rhs"), Err(ExpressionError::NonLinear { node: "lhs".to_string() }));
+    let graph = "lhs: x - x\nrhs: 5".parse::<ExpressionGraph>().unwrap();
+    assert_eq!(graph.solve("x", "lhs", "rhs"), Err(ExpressionError::NoUniqueSolution));
+    let graph = "lhs: x / three\nthree: 3\nrhs: 1/2".parse::<ExpressionGraph>().unwrap();
+    assert_eq!(graph.solve("x", "lhs", "rhs"), Ok(rational(3, 2)));
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TestOp {
+    Cpy(i64, &'static str),
+    Inc(&'static str),
+    Dec(&'static str),
+    Jnz(&'static str, isize),
+    Slow,
+    Hlt
+}
+
+impl InstructionSet for TestOp {
+    fn cycles(&self) -> usize {
+        if let TestOp::Slow = self { 3 } else { 1 }
+    }
+
+    fn execute(&self, registers: &mut Registers) -> Control {
+        match self {
+            TestOp::Cpy(value, r) => registers.set(r, *value),
+            TestOp::Inc(r) => registers[r] += 1,
+            TestOp::Dec(r) => registers[r] -= 1,
+            TestOp::Jnz(r, offset) if registers[r] != 0 => return Control::Jump(*offset),
+            TestOp::Jnz(_, _) | TestOp::Slow => {},
+            TestOp::Hlt => return Control::Halt
+        }
+        Control::Next
+    }
+}
+
+#[test]
+fn machine_runs_with_jumps_and_cycles() {
+    // a = 3 * 2 by repeated increment
+    let program = vec![TestOp::Cpy(3, "b"), TestOp::Inc("a"), TestOp::Inc("a"), TestOp::Dec("b"), TestOp::Jnz("b", -3), TestOp::Slow];
+    let mut machine = Machine::new(program);
+    let mut samples = Vec::new();
+    assert_eq!(machine.run(|cycle, registers| samples.push((cycle, registers["a"]))), Outcome::Halted);
+    assert_eq!(machine.registers.get("a"), 6);
+    assert_eq!(machine.registers.get("unset"), 0);
+    assert_eq!(machine.cycle, 1 + 4 * 3 + 3);
+    assert_eq!(samples.len(), machine.cycle);
+    assert_eq!(samples[samples.len() - 3..], [(14, 6), (15, 6), (16, 6)]);
+    assert!(machine.halted());
+    assert!(!machine.step(|_, _| {}));
+}
+
+#[test]
+fn machine_breakpoints_and_halts() {
+    let program = vec![TestOp::Inc("a"), TestOp::Inc("a"), TestOp::Hlt, TestOp::Inc("a")];
+    let mut machine = Machine::new(program);
+    machine.breakpoints.insert(0);
+    machine.breakpoints.insert(1);
+    assert_eq!(machine.run(|_, _| {}), Outcome::Breakpoint(0));
+    assert_eq!(machine.registers["a"], 0);
+    assert_eq!(machine.run(|_, _| {}), Outcome::Breakpoint(1));
+    assert_eq!(machine.registers["a"], 1);
+    assert_eq!(machine.run(|_, _| {}), Outcome::Halted);
+    assert_eq!((machine.registers["a"], machine.pc), (2, 2));
+
+    let mut machine = Machine::new(vec![TestOp::Jnz("a", -1), TestOp::Jnz("one", -5)]);
+    machine.registers.set("a", 1);
+    assert_eq!(machine.run(|_, _| {}), Outcome::Halted);
+}
+
+#[test]
+fn machine_loop_detection() {
+    let program = vec![TestOp::Cpy(1, "a"), TestOp::Inc("b"), TestOp::Dec("b"), TestOp::Jnz("a", -2)];
+    let mut machine = Machine::new(program);
+    machine.detect_loops = true;
+    assert_eq!(machine.run(|_, _| {}), Outcome::Loop(1));
+    assert_eq!(machine.cycle, 4);
+}
+
+#[test]
+fn registers_unset_equals_zero() {
+    let mut a = Registers::default();
+    let b = Registers::default();
+    a.set("x", 0);
+    assert_eq!(a, b);
+    let hash = |r: &Registers| { let mut set = HashSet::new(); set.insert(r.clone()); set };
+    assert!(hash(&a).contains(&b));
+    a["x"] += 2;
+    assert_ne!(a, b);
+}
+
+const TRANSCRIPT: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+#[test]
+fn filesystem_from_transcript() {
+    let fs = FileSystem::from_transcript(TRANSCRIPT).unwrap();
+    assert_eq!(fs.entry(FileSystem::ROOT).size, 48381165);
+    let e = fs.find(FileSystem::ROOT, "/a/e").unwrap();
+    assert_eq!((fs.path(e), fs.entry(e).size), ("/a/e".to_string(), 584));
+    assert_eq!(fs.find(e, "../../d/k").map(|k| fs.entry(k).size), Some(7214296));
+    assert_eq!(fs.path(FileSystem::ROOT), "/");
+    assert_eq!(fs.entry(e).parent, fs.find(FileSystem::ROOT, "a"));
+    let small = fs.directories_with_size_at_most(100000);
+    assert_eq!(small.iter().map(|d| fs.entry(*d).size).sum::<u64>(), 95437);
+    let freed = fs.smallest_directory_of_at_least(30000000 - (70000000 - 48381165)).unwrap();
+    assert_eq!((fs.path(freed), fs.entry(freed).size), ("/d".to_string(), 24933642));
+}
+
+#[test]
+fn filesystem_replays_cd_root_and_repeated_listings() {
+    let fs = FileSystem::from_transcript("$ cd /\n$ cd x/y\n$ ls\n5 f\n$ cd /\n$ ls\n1 g\n$ cd /x/y\n$ ls\n5 f\n$ cd ../..\n$ ls\n2 g").unwrap();
+    assert_eq!(fs.entry(FileSystem::ROOT).size, 7);
+    assert_eq!(fs.entry(fs.find(0, "x").unwrap()).size, 5);
+    assert_eq!(fs.directories().count(), 3);
+}
+
+#[test]
+fn filesystem_transcript_errors() {
+    let error = FileSystem::from_transcript("$ cd /\n$ cd ..").unwrap_err();
+    assert_eq!((error.line, error.snippet.as_str()), (2, ".."));
+    let error = FileSystem::from_transcript("$ cd /\n$ ls\n12x a").unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (3, 1, "12x"));
+    let error = FileSystem::from_transcript("$ cd /\n10 a").unwrap_err();
+    assert_eq!(error.line, 2);
+    let error = FileSystem::from_transcript("$ rm -rf /").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 3));
+    let error = FileSystem::from_transcript("$ ls\n10 a\n$ cd a").unwrap_err();
+    assert_eq!(error.line, 3);
+    let error = FileSystem::from_transcript("$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n100 f\n$ cd /\n$ ls\n5 a").unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (9, 3, "a"));
+    let error = FileSystem::from_transcript("$ ls\n10 x\ndir x").unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (3, 5, "x"));
+}
+
+#[test]
+fn filesystem_name_clashes() {
+    let mut fs = FileSystem::new();
+    let a = fs.mkdir(FileSystem::ROOT, "a").unwrap();
+    let f = fs.add_file(a, "f", 100).unwrap();
+    assert_eq!(fs.add_file(FileSystem::ROOT, "a", 5), Err(FsError::DirectoryExists("/a".to_string())));
+    assert_eq!(fs.mkdir(a, "f"), Err(FsError::FileExists("/a/f".to_string())));
+    assert_eq!(fs.mkdir(f, "g"), Err(FsError::NotADirectory("/a/f".to_string())));
+    assert_eq!(fs.add_file(f, "g", 1).unwrap_err().to_string(), "/a/f is a file, not a directory");
+    assert_eq!((fs.entry(FileSystem::ROOT).size, fs.entry(a).size), (100, 100));
+    assert_eq!(fs.mkdir(FileSystem::ROOT, "a"), Ok(a));
+    assert_eq!(fs.entries.len(), 3);
+}
+
+fn net_tiles(net: &str, scale: i32) -> Vec<Coordinate<i32>> {
+    net.lines().enumerate()
+        .flat_map(|(y, line)| line.chars().enumerate().filter(|(_, c)| *c == '#').map(move |(x, _)| (x as i32, y as i32)))
+        .flat_map(|(x, y)| (0..scale * scale).map(move |i| Coordinate { x: x * scale + i % scale, y: y * scale + i / scale }))
+        .collect()
+}
+
+#[test]
+fn direction_rotation() {
+    assert_eq!(Direction::North.rotate_clockwise(), Direction::East);
+    assert_eq!(Direction::West.rotate_clockwise(), Direction::North);
+    assert_eq!(Direction::North.rotate_anticlockwise(), Direction::West);
+    assert_eq!(Direction::SouthEast.opposite(), Direction::NorthWest);
+}
+
+#[test]
+fn cube_net_wraps_are_reversible() {
+    let nets = [
+        "#\n####\n#", "#\n####\n.#", "#\n####\n..#", "#\n####\n...#", ".#\n####\n.#", ".#\n####\n..#",
+        "##\n.###\n.#", "##\n.###\n..#", "##\n.###\n...#", "##\n.##\n..##", "###\n..###",
+    ];
+    for net in nets {
+        for scale in [1, 4] {
+            let tiles = net_tiles(net, scale);
+            let cube = CubeNet::fold(&tiles).unwrap_or_else(|e| panic!("{:?} didn't fold: {}", net, e));
+            assert_eq!(cube.face_size, scale);
+            let tile_set = tiles.iter().copied().collect::<HashSet<_>>();
+            for tile in tiles.iter() {
+                for facing in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                    let (next, next_facing) = cube.wrap(*tile, facing).unwrap();
+                    assert!(tile_set.contains(&next), "{:?}: {} {:?} left the net", net, tile, facing);
+                    assert_eq!(cube.wrap(next, next_facing.opposite()), Some((*tile, facing.opposite())), "{:?}: {} {:?}", net, tile, facing);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cube_net_example_layout() {
+    let tiles = net_tiles("..#\n###\n..##", 4);
+    let cube = CubeNet::fold(&tiles).unwrap();
+    assert_eq!(cube.face_size, 4);
+    // The examples from the puzzle text: A -> B, C -> D
+    assert_eq!(cube.wrap(Coordinate { x: 11, y: 5 }, Direction::East), Some((Coordinate { x: 14, y: 8 }, Direction::South)));
+    assert_eq!(cube.wrap(Coordinate { x: 10, y: 11 }, Direction::South), Some((Coordinate { x: 1, y: 7 }, Direction::North)));
+    assert_eq!(cube.wrap(Coordinate { x: 11, y: 5 }, Direction::SouthEast), None);
+    assert_eq!(cube.wrap(Coordinate { x: 0, y: 0 }, Direction::East), None);
+}
+
+#[test]
+fn cube_net_errors() {
+    assert_eq!(CubeNet::fold(&net_tiles("####\n####", 1)), Err(CubeNetError::TileCount(8)));
+    assert_eq!(CubeNet::fold(&net_tiles("###\n###", 1)), Err(CubeNetError::Overlap));
+    assert_eq!(CubeNet::fold(&net_tiles("######", 1)), Err(CubeNetError::Overlap));
+    assert_eq!(CubeNet::fold(&net_tiles("##.##\n.#..#", 1)), Err(CubeNetError::Faces { corner: None }));
+    let mut tiles = net_tiles("#\n####\n#", 2);
+    tiles.retain(|t| *t != Coordinate { x: 0, y: 0 });
+    tiles.push(Coordinate { x: 5, y: 0 });
+    assert_eq!(CubeNet::fold(&tiles), Err(CubeNetError::Faces { corner: Some(Coordinate { x: 0, y: 0 }) }));
+}
+
+#[test]
+fn bounded_topology_stops_at_edges() {
+    let grid = Bounded(Rectangle::new(Coordinate { x: 0, y: 0 }, Coordinate { x: 3, y: 2 }));
+    assert_eq!(grid.step(Coordinate { x: 3, y: 1 }, Direction::East), None);
+    assert_eq!(grid.step(Coordinate { x: 2, y: 1 }, Direction::SouthEast), Some((Coordinate { x: 3, y: 2 }, Direction::SouthEast)));
+    assert_eq!(grid.advance(Coordinate { x: 0, y: 0 }, Direction::East, 3), Some((Coordinate { x: 3, y: 0 }, Direction::East)));
+    assert_eq!(grid.advance(Coordinate { x: 0, y: 0 }, Direction::East, 4), None);
+    assert_eq!(grid.walk(Coordinate { x: 0, y: 0 }, Direction::East, 10, |c| c.x != 2), (Coordinate { x: 1, y: 0 }, Direction::East));
+    assert_eq!(grid.walk(Coordinate { x: 0, y: 2 }, Direction::East, 10, |_| true), (Coordinate { x: 3, y: 2 }, Direction::East));
+}
+
+#[test]
+fn torus_topology_wraps_in_constant_time() {
+    let valley = Torus(Rectangle::new(Coordinate { x: 1, y: 1 }, Coordinate { x: 6, y: 4 }));
+    assert_eq!(valley.step(Coordinate { x: 6, y: 2 }, Direction::East), Some((Coordinate { x: 1, y: 2 }, Direction::East)));
+    assert_eq!(valley.step(Coordinate { x: 1, y: 1 }, Direction::NorthWest), Some((Coordinate { x: 6, y: 4 }, Direction::NorthWest)));
+    for steps in [0, 1, 5, 23, 1000] {
+        for facing in Direction::ALL {
+            let start = Coordinate { x: 3, y: 2 };
+            let slow = (0..steps).try_fold((start, facing), |(p, f), _| valley.step(p, f));
+            assert_eq!(valley.advance(start, facing, steps), slow);
+        }
+    }
+    let blizzard = Particle { starting_point: Coordinate { x: 2, y: 4 }, heading: Direction::South };
+    assert_eq!(blizzard.position_after(1, &valley), Some((Coordinate { x: 2, y: 1 }, Direction::South)));
+    assert_eq!(blizzard.position_after(9, &valley), Some((Coordinate { x: 2, y: 1 }, Direction::South)));
+}
+
+#[test]
+fn ragged_and_cube_topologies_share_a_walker() {
+    let tiles = net_tiles("..#\n###\n..##", 4);
+    let ragged = RaggedWrap::new(&tiles);
+    let cube = CubeNet::fold(&tiles).unwrap();
+    assert_eq!(ragged.step(Coordinate { x: 11, y: 5 }, Direction::East), Some((Coordinate { x: 0, y: 5 }, Direction::East)));
+    assert_eq!(ragged.step(Coordinate { x: 5, y: 4 }, Direction::North), Some((Coordinate { x: 5, y: 7 }, Direction::North)));
+    assert_eq!(ragged.step(Coordinate { x: 14, y: 11 }, Direction::South), Some((Coordinate { x: 14, y: 8 }, Direction::South)));
+    fn walk_east<T: Topology>(topology: &T) -> (Coordinate<i32>, Direction) {
+        topology.walk(Coordinate { x: 10, y: 5 }, Direction::East, 3, |c| *c != Coordinate { x: 14, y: 10 })
+    }
+    assert_eq!(walk_east(&ragged), (Coordinate { x: 1, y: 5 }, Direction::East));
+    assert_eq!(walk_east(&cube), (Coordinate { x: 14, y: 9 }, Direction::South));
+    // Diagonal facings aren't supported, so a walker stays put rather than crashing
+    fn walk_diagonally<T: Topology>(topology: &T) -> Option<(Coordinate<i32>, Direction)> {
+        topology.advance(Coordinate { x: 10, y: 5 }, Direction::SouthEast, 1)
+    }
+    assert_eq!(walk_diagonally(&ragged), None);
+    assert_eq!(walk_diagonally(&cube), None);
+    assert_eq!(ragged.walk(Coordinate { x: 10, y: 5 }, Direction::SouthEast, 3, |_| true), (Coordinate { x: 10, y: 5 }, Direction::SouthEast));
+}
+
+#[test]
+fn circular_list_matches_naive_mixing() {
+    let numbers = (0..200_i64).map(|i| (i * 7919) % 541 - 270).collect::<Vec<_>>();
+    let mut list = numbers.iter().copied().collect::<CircularList<_>>();
+    let mut naive = numbers.iter().copied().enumerate().collect::<Vec<_>>();
+    for _ in 0..3 {
+        for (id, value) in numbers.iter().enumerate() {
+            list.move_by(id, *value * 811589153);
+            let current = naive.iter().position(|(i, _)| *i == id).unwrap();
+            let entry = naive.remove(current);
+            let new_index = (current as i64 + *value * 811589153).rem_euclid(naive.len() as i64) as usize;
+            naive.insert(new_index, entry);
+        }
+    }
+    assert_eq!(list.ids().collect::<Vec<_>>(), naive.iter().map(|(i, _)| *i).collect::<Vec<_>>());
+    for (position, (id, _)) in naive.iter().enumerate() {
+        assert_eq!(list.position(*id), position);
+        assert_eq!(list.id_at(position + naive.len()), *id);
+    }
+    assert_eq!(*list.nth_after(naive[5].0, -6), naive[199].1);
+}
+
+#[test]
+fn circular_list_small_cases() {
+    let mut single = CircularList::from_iter(["only"]);
+    single.move_by(0, 5);
+    assert_eq!(single.iter().collect::<Vec<_>>(), vec![&"only"]);
+    assert_eq!(*single.nth_after(0, 1000), "only");
+    let mut triple = CircularList::from_iter(['a', 'b', 'c']);
+    triple.move_by(0, 2);
+    assert_eq!(triple.iter().collect::<String>(), "abc".to_string());
+    triple.move_by(0, 1);
+    assert_eq!(triple.iter().collect::<String>(), "bac".to_string());
+    triple.move_by(2, -1);
+    assert_eq!(triple.iter().collect::<String>(), "bca".to_string());
+    assert_eq!(triple.find(|c| *c == 'a'), Some(0));
+    assert!(CircularList::<i32>::from_iter([]).is_empty());
+}
+
+#[test]
+fn crate_stacks_infer_stack_count_from_footer() {
+    let drawing = "                                    [Q]\n[A]                                 [R]\n[B] [C] [D] [E] [F] [G] [H] [I] [J] [K]\n 1   2   3   4   5   6   7   8   9   10 ";
+    let stacks = drawing.parse::<CrateStacks>().unwrap();
+    assert_eq!(stacks.stacks.len(), 10);
+    assert_eq!(stacks.stacks[0], vec!['B', 'A']);
+    assert_eq!(stacks.stacks[9], vec!['K', 'R', 'Q']);
+    let narrow = "[X]\n[Y][Z]\n 1  2".parse::<CrateStacks>().unwrap();
+    assert_eq!(narrow.stacks, vec![vec!['Y', 'X'], vec!['Z']]);
+}
+
+#[test]
+fn crate_stack_parse_errors() {
+    let error = "[A]\n 1   2".replace("[A]", "  [A]").parse::<CrateStacks>().unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (1, 4, "A"));
+    let error = "[A]\n    [B]\n 1   2".parse::<CrateStacks>().unwrap_err();
+    assert_eq!((error.line, error.column, error.expected.as_str()), (1, 2, "a crate resting on another crate"));
+    let error = "[A] [B]\n 1   3".parse::<CrateStacks>().unwrap_err();
+    assert_eq!((error.line, error.snippet.as_str()), (2, "3"));
+    assert!("".parse::<CrateStacks>().is_err());
+}
+
+struct ReverseThenBatch;
+
+impl CraneModel for ReverseThenBatch {
+    fn place(&self, mut lifted: Vec<char>, destination: &mut Vec<char>) {
+        lifted.sort();
+        destination.append(&mut lifted);
+    }
+}
+
+#[test]
+fn crate_stack_moves() {
+    let mut stacks = CrateStacks { stacks: vec![vec!['Z', 'A', 'M'], vec![]] };
+    stacks.apply(&ReverseThenBatch, &"move 3 from 1 to 2".parse().unwrap()).unwrap();
+    assert_eq!(stacks.stacks, vec![vec![], vec!['A', 'M', 'Z']]);
+    stacks.apply(&SingleMove, &CraneMove { quantity: 2, origin: 2, destination: 2 }).unwrap();
+    assert_eq!(stacks.stacks[1], vec!['A', 'Z', 'M']);
+    let before = stacks.clone();
+    let moves = ["move 1 from 2 to 1", "move 2 from 1 to 2"].map(|m| m.parse::<CraneMove>().unwrap());
+    let error = stacks.apply_all(&BatchMove, &moves).unwrap_err();
+    assert_eq!(error, StackError::NotEnoughCrates { instruction: Some(2), stack: 1, requested: 2, available: 1 });
+    assert_eq!(error.to_string(), "Instruction 2: Can't take 2 crates from stack 1, which only has 1");
+    assert_eq!(stacks.stacks, vec![vec!['M'], vec!['A', 'Z']]);
+    assert_ne!(stacks, before);
+    let error = stacks.apply(&BatchMove, &CraneMove { quantity: 1, origin: 1, destination: 3 }).unwrap_err();
+    assert_eq!(error, StackError::NoSuchStack { instruction: None, stack: 3 });
+    assert_eq!(error.to_string(), "There is no stack 3");
+    assert_eq!(stacks.stacks, vec![vec!['M'], vec!['A', 'Z']]);
+}
+
+#[test]
+fn worry_expressions() {
+    for (text, old, expected, printed) in [
+        ("old * old", 7, 49, "old * old"),
+        ("old + old", 7, 14, "old + old"),
+        ("old - 3 - 2", 7, 2, "old - 3 - 2"),
+        ("old - (3 - 2)", 7, 6, "old - (3 - 2)"),
+        ("2 + old * 3", 7, 23, "2 + old * 3"),
+        ("(2+old)*3", 7, 27, "(2 + old) * 3"),
+    ] {
+        let expression = text.parse::<WorryExpression>().unwrap();
+        assert_eq!(expression.evaluate(old, None), expected, "{}", text);
+        assert_eq!(expression.to_string(), printed);
+        assert_eq!(printed.parse::<WorryExpression>().unwrap(), expression);
+    }
+    assert_eq!("old - 10".parse::<WorryExpression>().unwrap().evaluate(3, Some(5)), 3);
+    let error = "old * (old + 1".parse::<WorryExpression>().unwrap_err();
+    assert_eq!((error.column, error.expected.as_str()), (15, "')'"));
+    let error = "old * new".parse::<WorryExpression>().unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (7, "new"));
+}
+
+const MONKEYS: &str = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
+
+#[test]
+fn monkey_simulation_example() {
+    let monkeys = sections::<Vec<Monkey>>(MONKEYS).unwrap();
+    assert_eq!(monkeys[2].operation, WorryExpression::Operation(Box::new(WorryExpression::Old), WorryOperator::Multiply, Box::new(WorryExpression::Old)));
+    let mut simulation = MonkeySimulation::new(monkeys.clone(), Relief::Divide(3)).unwrap();
+    simulation.record_trajectories = true;
+    let report = simulation.run(1);
+    assert_eq!(report.holdings.iter().map(|h| h.iter().map(|(_, w)| *w).collect::<Vec<_>>()).collect::<Vec<_>>(),
+               vec![vec![20, 23, 27, 26], vec![2080, 25, 167, 207, 401, 1046], vec![], vec![]]);
+    assert_eq!(report.trajectories[0], vec![Throw { round: 1, from: 0, to: 3, worry: 500 }, Throw { round: 1, from: 3, to: 1, worry: 167 }]);
+    assert_eq!(report.total_inspections(), report.trajectories.iter().map(|t| t.len()).sum::<usize>());
+    let report = simulation.run(20);
+    assert_eq!(report.inspections, vec![101, 95, 7, 105]);
+    assert_eq!(report.most_active(), vec![3, 0, 1, 2]);
+    assert_eq!(report.monkey_business(), 10605);
+    let simulation = MonkeySimulation::new(monkeys, Relief::Unchanged).unwrap();
+    assert_eq!(simulation.run(10000).monkey_business(), 2713310158);
+}
+
+#[test]
+fn monkey_simulation_errors() {
+    let monkeys = sections::<Vec<Monkey>>(MONKEYS).unwrap();
+    assert_eq!(MonkeySimulation::new(monkeys.clone(), Relief::Divide(0)), Err(MonkeyError::InvalidRelief(0)));
+    assert_eq!(MonkeySimulation::new(monkeys[..3].to_vec(), Relief::Divide(3)), Err(MonkeyError::UnknownTarget { from: 0, to: 3 }));
+    let mut zero_divisor = monkeys.clone();
+    zero_divisor[1].test_divisor = 0;
+    assert_eq!(MonkeySimulation::new(zero_divisor, Relief::Unchanged), Err(MonkeyError::InvalidDivisor { monkey: 1, divisor: 0 }));
+    // Throws go by position, so the ids have to line up with it
+    let mut swapped = monkeys.clone();
+    swapped.swap(0, 1);
+    assert_eq!(MonkeySimulation::new(swapped, Relief::Unchanged), Err(MonkeyError::MisplacedMonkey { position: 0, id: 1 }));
+}
+
+#[test]
+fn monkey_parse_errors() {
+    let error = "Monkey 0:\n  Starting items: 79, x8\n  Operation: new = old * 19\n  Test: divisible by 23\n  If true: throw to monkey 2\n  If false: throw to monkey 3".parse::<Monkey>().unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (2, 23, "x8"));
+    let error = "Monkey 0:\n  Starting items: 79\n  Operation: new = old / 19\n  Test: divisible by 23\n  If true: throw to monkey 2\n  If false: throw to monkey 3".parse::<Monkey>().unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (3, 24, "/"));
+    let error = "Monkey 0:\n  Starting items: 79\n  Operation: new = old\n  Test: divisible by 0\n  If true: throw to monkey 2\n  If false: throw to monkey 3".parse::<Monkey>().unwrap_err();
+    assert_eq!((error.line, error.column, error.expected.as_str()), (4, 22, "a positive divisor"));
+    assert!("Monkey 0:\n  Starting items: 79".parse::<Monkey>().is_err());
+}
+
+fn naive_sand(rocks: &HashSet<Coordinate<i32>>, spawn: Coordinate<i32>, floor: i32) -> HashSet<Coordinate<i32>> {
+    let mut filled = rocks.clone();
+    let mut sand = HashSet::new();
+    while !filled.contains(&spawn) {
+        let mut grain = spawn;
+        while let Some(next) = [0, -1, 1].iter().map(|dx| Coordinate { x: grain.x + dx, y: grain.y + 1 }).find(|n| !filled.contains(n) && n.y < floor) {
+            grain = next;
+        }
+        filled.insert(grain);
+        sand.insert(grain);
+    }
+    sand
+}
+
+#[test]
+fn sand_simulation_matches_naive_settling() {
+    let rocks = [(498, 4), (498, 5), (498, 6), (497, 6), (496, 6), (503, 4), (502, 4), (502, 5), (502, 6), (502, 7), (502, 8), (502, 9),
+                 (501, 9), (500, 9), (499, 9), (498, 9), (497, 9), (496, 9), (495, 9), (494, 9)]
+        .map(|(x, y)| Coordinate { x, y });
+    let mut sand = SandSimulation::new(rocks);
+    assert_eq!(sand.fill(), 24);
+    assert_eq!(sand.drop_grain(), Grain::Lost);
+    let mut sand = sand.with_bottom(Bottom::Floor(11));
+    assert_eq!(sand.fill(), 93 - 24);
+    assert_eq!(sand.drop_grain(), Grain::Blocked);
+    let rock_set = rocks.iter().copied().collect::<HashSet<_>>();
+    assert_eq!(*sand.settled(), naive_sand(&rock_set, Coordinate { x: 500, y: 0 }, 11));
+    assert_eq!(sand.frame().get(&Coordinate { x: 500, y: 0 }), Some('o'));
+}
+
+#[test]
+fn sand_simulation_with_several_spawns_and_custom_moves() {
+    let floor = (0..20).map(|x| Coordinate { x, y: 10 });
+    let mut sand = SandSimulation::new(floor).with_spawn(Coordinate { x: 5, y: 0 }).with_moves(&[Direction::South, Direction::SouthEast]).unwrap();
+    for _ in 0..30 {
+        for spawn in [Coordinate { x: 5, y: 0 }, Coordinate { x: 8, y: 3 }] {
+            sand.drop_from(spawn);
+        }
+    }
+    // Grains only ever slide to the right, so nothing ends up left of the first spawn point
+    assert!(sand.settled().iter().all(|g| g.x >= 5));
+    assert_eq!(sand.drop_from(Coordinate { x: 5, y: 0 }), Grain::Blocked);
+    // Changing the moves forgets the remembered paths, so every grain here starts from scratch
+    let moves = [Direction::South, Direction::SouthEast];
+    let mut replay = SandSimulation::new((0..20).map(|x| Coordinate { x, y: 10 }));
+    for _ in 0..30 {
+        for spawn in [Coordinate { x: 5, y: 0 }, Coordinate { x: 8, y: 3 }] {
+            replay = replay.with_moves(&moves).unwrap();
+            replay.drop_from(spawn);
+        }
+    }
+    assert_eq!(replay.settled(), sand.settled());
+    // Sideways or upward moves could leave a grain wandering forever
+    for direction in [Direction::East, Direction::West, Direction::North, Direction::NorthEast] {
+        assert_eq!(replay.clone().with_moves(&[Direction::South, direction]), Err(SandError::NotSouthward(direction)));
+    }
+}
+
+#[test]
+fn diamond_row_slices_and_coverage() {
+    let diamond = Diamond::reaching(Coordinate { x: 8, y: 7 }, &Coordinate { x: 2, y: 10 });
+    assert_eq!(diamond.radius, 9);
+    assert_eq!(diamond.row_slice(7), Some(-1..=17));
+    assert_eq!(diamond.row_slice(16), Some(8..=8));
+    assert_eq!(diamond.row_slice(17), None);
+    assert_eq!(diamond.rotated_bounds(), (6..=24, -8..=10));
+    let diamonds = [Diamond::new(Coordinate { x: 0, y: 0 }, 2), Diamond::new(Coordinate { x: 4, y: 1 }, 1), Diamond::new(Coordinate { x: 10, y: 0 }, 1)];
+    assert_eq!(row_coverage(&diamonds, 1), vec![-1..=1, 3..=5, 10..=10]);
+    assert_eq!(row_coverage(&diamonds, 0), vec![-2..=2, 4..=4, 9..=11]);
+    let touching = [Diamond::new(Coordinate { x: 0, y: 0 }, 1), Diamond::new(Coordinate { x: 3, y: 0 }, 1), Diamond::new(Coordinate { x: 1, y: 0 }, 0)];
+    assert_eq!(row_coverage(&touching, 0), vec![-1..=4]);
+    assert_eq!(row_coverage(&diamonds, 5), vec![]);
+}
+
+#[test]
+fn uncovered_points_matches_brute_force() {
+    let diamonds = [((2, 18), 7), ((9, 16), 1), ((13, 2), 3), ((12, 14), 4), ((10, 20), 4), ((14, 17), 5), ((8, 7), 9),
+                    ((2, 0), 10), ((0, 11), 3), ((20, 14), 8), ((17, 20), 6), ((16, 7), 5), ((14, 3), 1), ((20, 1), 7)]
+        .map(|((x, y), radius)| Diamond::new(Coordinate { x, y }, radius));
+    let bounds = Rectangle::new(Coordinate { x: 0, y: 0 }, Coordinate { x: 20, y: 20 });
+    assert_eq!(uncovered_points(&diamonds, &bounds), vec![Coordinate { x: 14, y: 11 }]);
+    // Near the edges of a smaller area, the gaps are bounded by its sides
+    let bounds = Rectangle::new(Coordinate { x: 0, y: 0 }, Coordinate { x: 30, y: 30 });
+    let brute_force = (0..=30).flat_map(|y| (0..=30).map(move |x| Coordinate { x, y }))
+        .filter(|c| !diamonds.iter().any(|d| d.contains(c)))
+        .collect::<HashSet<_>>();
+    let found = uncovered_points(&diamonds, &bounds);
+    assert!(!found.is_empty());
+    assert!(found.iter().all(|c| brute_force.contains(c)));
+    assert!(found.contains(&Coordinate { x: 14, y: 11 }));
+}
+
+fn valve_network(description: &[(&str, i64, &str)]) -> ValveNetwork {
+    ValveNetwork::new(description.iter().map(|(name, rate, tunnels)| (name.to_string(), *rate, tunnels.split(", ").map(String::from).collect()))).unwrap()
+}
+
+/// Tries every order of opening every subset of `valves`, for a single agent
+fn brute_force_pressure(network: &ValveNetwork, from: usize, time: i64, valves: &[usize]) -> i64 {
+    valves.iter().map(|next| {
+        let remaining = time - network.distances_from(from)[*next].unwrap() - 1;
+        if remaining <= 0 {
+            return 0;
+        }
+        let others = valves.iter().copied().filter(|v| v != next).collect::<Vec<_>>();
+        remaining * network.flow_rates[*next] + brute_force_pressure(network, *next, remaining, &others)
+    }).max().unwrap_or(0)
+}
+
+/// Tries every way of sharing `valves` out between `agents` agents, who each open their share
+/// in the best order
+fn brute_force_agents(network: &ValveNetwork, from: usize, time: i64, valves: &[usize], agents: usize) -> i64 {
+    match agents {
+        0 => 0,
+        1 => brute_force_pressure(network, from, time, valves),
+        _ => (0..1_usize << valves.len()).map(|mask| {
+            let (mine, theirs): (Vec<_>, Vec<_>) = valves.iter().enumerate().partition(|(bit, _)| mask & 1 << bit != 0);
+            let mine = mine.into_iter().map(|(_, v)| *v).collect::<Vec<_>>();
+            let theirs = theirs.into_iter().map(|(_, v)| *v).collect::<Vec<_>>();
+            brute_force_pressure(network, from, time, &mine) + brute_force_agents(network, from, time, &theirs, agents - 1)
+        }).max().unwrap()
+    }
+}
+
+#[test]
+fn valve_network_example() {
+    let network = valve_network(&[("AA", 0, "DD, II, BB"), ("BB", 13, "CC, AA"), ("CC", 2, "DD, BB"), ("DD", 20, "CC, AA, EE"), ("EE", 3, "FF, DD"),
+                                  ("FF", 0, "EE, GG"), ("GG", 0, "FF, HH"), ("HH", 22, "GG"), ("II", 0, "AA, JJ"), ("JJ", 21, "II")]);
+    assert_eq!(network.best_pressure("AA", 30, 1), Ok(1651));
+    assert_eq!(network.best_pressure("AA", 26, 2), Ok(1707));
+    let working = network.working_valves();
+    for (start, agents) in [("AA", 3), ("AA", 6), ("DD", 2)] {
+        let from = network.index_of(start).unwrap();
+        assert_eq!(network.best_pressure(start, 26, agents), Ok(brute_force_agents(&network, from, 26, &working, agents)), "{} agents from {}", agents, start);
+    }
+    assert_eq!(network.best_pressure("AA", 26, 0), Ok(0));
+    assert_eq!(network.best_pressure("JJ", 1, 1), Ok(0));
+    let best = network.best_by_subset("AA", 30).unwrap();
+    assert_eq!(best.len(), 1 << 6);
+    assert_eq!(best[best.len() - 1], 1651);
+}
+
+#[test]
+fn valve_network_uneven_split() {
+    // One agent should fetch the distant valve, while the other opens all of the nearby ones
+    let network = valve_network(&[("AA", 0, "B, C, D, E, F, X1"), ("B", 5, "AA"), ("C", 5, "AA"), ("D", 5, "AA"), ("E", 5, "AA"), ("F", 5, "AA"),
+                                  ("X1", 0, "AA, X2"), ("X2", 0, "X1, X3"), ("X3", 100, "X2")]);
+    let working = network.working_valves();
+    let start = network.index_of("AA").unwrap();
+    assert_eq!(network.best_pressure("AA", 12, 2), Ok(brute_force_agents(&network, start, 12, &working, 2)));
+    assert_eq!(network.best_pressure("AA", 12, 1), Ok(brute_force_pressure(&network, start, 12, &working)));
+}
+
+#[test]
+fn valve_network_errors() {
+    let error = ValveNetwork::new([("AA".to_string(), 0, vec!["BB".to_string()])]).unwrap_err();
+    assert_eq!(error, ValveError::UnknownValve { name: "BB".to_string(), referenced_by: Some("AA".to_string()) });
+    assert_eq!(error.to_string(), "Valve AA has a tunnel to unknown valve BB");
+    let network = valve_network(&[("AA", 0, "AA")]);
+    assert_eq!(network.best_pressure("ZZ", 30, 1), Err(ValveError::UnknownValve { name: "ZZ".to_string(), referenced_by: None }));
+    let many = (0..=MAX_WORKING_VALVES).map(|i| (i.to_string(), 1, vec![]));
+    assert_eq!(ValveNetwork::new(many), Err(ValveError::TooManyValves(MAX_WORKING_VALVES + 1)));
+    let duplicated = [("AA", 0, "BB"), ("BB", 3, "AA"), ("AA", 5, "BB")].map(|(name, rate, tunnel)| (name.to_string(), rate, vec![tunnel.to_string()]));
+    assert_eq!(ValveNetwork::new(duplicated), Err(ValveError::DuplicateValve("AA".to_string())));
+}
+
+#[test]
+fn tetris_pieces_parse() {
+    let piece = "\n....\n.##.\n..#.\n\n".parse::<Piece>().unwrap();
+    assert_eq!((piece.width(), piece.height()), (2, 2));
+    assert_eq!(piece.rows(), &[0b10, 0b11]);
+    let pieces = Piece::standard();
+    assert_eq!(pieces.iter().map(|p| (p.width(), p.height())).collect::<Vec<_>>(), vec![(4, 1), (3, 3), (3, 3), (1, 4), (2, 2)]);
+    // The L is drawn upright, so its bottom row is the full one
+    assert_eq!(pieces[2].rows(), &[0b111, 0b100, 0b100]);
+
+    let error = parse_pieces("##\n\n#.\n#x").unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet.as_str()), (4, 2, "x"));
+    // Windows line endings and extra blank lines still separate the pieces
+    let crlf = parse_pieces("####\r\n\r\n\r\n.#.\r\n###\r\n.#.\r\n").unwrap();
+    assert_eq!(crlf, Piece::standard()[..2]);
+    let error = parse_pieces("##\r\n\r\n\r\n#.\r\n#x").unwrap_err();
+    assert_eq!((error.line, error.column), (5, 2));
+    assert!("..\n..".parse::<Piece>().is_err());
+    let error = parse_jets("<<>-<\n").unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (4, "-"));
+    assert_eq!(parse_jets("<>\n"), Ok(vec![Direction::West, Direction::East]));
+}
+
+#[test]
+fn tetris_cycles_match_simulation() {
+    let pieces = parse_pieces("###\n\n#.\n##\n\n#\n#").unwrap();
+    let jets = parse_jets("<<><>>><<<>><<>>>>><<<").unwrap();
+    for (width, spawn) in [(5, Coordinate { x: 1, y: 2 }), (7, Coordinate { x: 2, y: 3 }), (6, Coordinate { x: 0, y: 1 })] {
+        let mut chamber = TetrisChamber::with_layout(pieces.clone(), jets.clone(), width, spawn).unwrap();
+        let predicted = (0..3000).step_by(37).map(|n| chamber.height_after(n).unwrap()).collect::<Vec<_>>();
+        let simulated = (0..3000).map(|_| { let height = chamber.height(); chamber.drop_piece(); height }).step_by(37).collect::<Vec<_>>();
+        assert_eq!(predicted, simulated);
+        assert!(chamber.rows().iter().all(|r| *r < 1 << width));
+    }
+}
+
+#[test]
+fn tetris_resting_places() {
+    let mut chamber = TetrisChamber::with_layout(parse_pieces("##\n\n#").unwrap(), vec![Direction::West], 3, Coordinate { x: 1, y: 0 }).unwrap();
+    assert_eq!(chamber.drop_piece(), Coordinate { x: 0, y: 0 });
+    assert_eq!(chamber.drop_piece(), Coordinate { x: 0, y: 1 });
+    assert_eq!(chamber.drop_piece(), Coordinate { x: 0, y: 2 });
+    assert_eq!((chamber.height(), chamber.dropped()), (3, 3));
+    assert_eq!(chamber.to_string(), "|##.|\n|#..|\n|##.|\n+---+");
+    assert_eq!(chamber.height_after(1), Ok(1));
+}
+
+#[test]
+fn tetris_errors() {
+    let pieces = parse_pieces("##\n\n#").unwrap();
+    let spawn = Coordinate { x: 0, y: 3 };
+    assert_eq!(TetrisChamber::new(Vec::new(), vec![Direction::West]), Err(TetrisError::NoPieces));
+    assert_eq!(TetrisChamber::new(pieces.clone(), Vec::new()), Err(TetrisError::NoJets));
+    assert_eq!(TetrisChamber::new(pieces.clone(), vec![Direction::West, Direction::South]), Err(TetrisError::InvalidJet(Direction::South)));
+    assert_eq!(TetrisChamber::with_layout(pieces.clone(), vec![Direction::West], 0, spawn), Err(TetrisError::InvalidWidth(0)));
+    assert_eq!(TetrisChamber::with_layout(pieces.clone(), vec![Direction::West], 65, spawn), Err(TetrisError::InvalidWidth(65)));
+    assert_eq!(TetrisChamber::with_layout(pieces.clone(), vec![Direction::West], 1, spawn), Err(TetrisError::PieceDoesNotFit(0)));
+    assert!(TetrisChamber::with_layout(pieces, vec![Direction::West], 64, Coordinate { x: 62, y: 0 }).is_ok());
+    // Everything slides to the left wall, so the right-hand column is never filled and the top
+    // of the tower never repeats
+    let mut chamber = TetrisChamber::with_layout(parse_pieces("#").unwrap(), vec![Direction::West], 2, spawn).unwrap();
+    assert_eq!(chamber.height_after(MAX_SIMULATED_PIECES), Ok(MAX_SIMULATED_PIECES));
+    assert_eq!(chamber.height_after(MAX_SIMULATED_PIECES + 1), Err(TetrisError::NoCycle));
+    chamber.drop_piece();
+    assert_eq!(chamber.height_after(MAX_SIMULATED_PIECES + 1), Ok(MAX_SIMULATED_PIECES + 1));
+}
+
+/// Replays a plan minute by minute, checking every build can be afforded, and returns the
+/// amount of `target` gathered
+fn replay_plan(factory: &Factory, producers: &[(&str, u32)], time: u32, target: &str, plan: &ProductionPlan) -> u32 {
+    let resources = factory.resources();
+    let index_of = |name: &str| resources.iter().position(|r| r == name).unwrap();
+    let mut stock = vec![0; resources.len()];
+    let mut rates = vec![0; resources.len()];
+    for (name, count) in producers {
+        rates[index_of(name)] += count;
+    }
+    for minute in 1..=time {
+        let build = plan.builds.iter().find(|b| b.minute == minute).map(|b| &factory.recipes()[b.recipe]);
+        if let Some(recipe) = build {
+            for (name, amount) in recipe.costs.iter() {
+                assert!(stock[index_of(name)] >= *amount, "Can't afford {} in minute {}", recipe, minute);
+                stock[index_of(name)] -= amount;
+            }
+        }
+        for (resource, rate) in rates.iter().enumerate() {
+            stock[resource] += rate;
+        }
+        if let Some(recipe) = build {
+            rates[index_of(&recipe.produces)] += 1;
+        }
+    }
+    stock[index_of(target)]
+}
+
+/// Tries every possible build (or none) in every minute
+fn brute_force_production(factory: &Factory, stock: Vec<u32>, rates: Vec<u32>, remaining: u32, target: usize,
+                          cache: &mut HashMap<(Vec<u32>, Vec<u32>, u32), u32>) -> u32 {
+    if remaining == 0 {
+        return stock[target];
+    }
+    if let Some(best) = cache.get(&(stock.clone(), rates.clone(), remaining)) {
+        return *best;
+    }
+    let resources = factory.resources();
+    let gathered = stock.iter().zip(rates.iter()).map(|(s, r)| s + r).collect::<Vec<_>>();
+    let mut best = brute_force_production(factory, gathered.clone(), rates.clone(), remaining - 1, target, cache);
+    for recipe in factory.recipes() {
+        let mut after = gathered.clone();
+        let affordable = recipe.costs.iter().all(|(name, amount)| {
+            let resource = resources.iter().position(|r| r == name).unwrap();
+            stock[resource] >= *amount && { after[resource] -= amount; true }
+        });
+        if affordable {
+            let mut more_rates = rates.clone();
+            more_rates[resources.iter().position(|r| *r == recipe.produces).unwrap()] += 1;
+            best = best.max(brute_force_production(factory, after, more_rates, remaining - 1, target, cache));
+        }
+    }
+    cache.insert((stock, rates, remaining), best);
+    best
+}
+
+#[test]
+fn recipe_parsing() {
+    let recipe = "Each obsidian robot costs 3 ore and 14 clay.".parse::<Recipe>().unwrap();
+    assert_eq!(recipe, Recipe { produces: "obsidian".to_string(), costs: vec![("ore".to_string(), 3), ("clay".to_string(), 14)] });
+    assert_eq!(recipe.to_string(), "Each obsidian robot costs 3 ore and 14 clay.");
+    assert_eq!("Each gold robot costs 1 lead".parse::<Recipe>().unwrap().costs, vec![("lead".to_string(), 1)]);
+    let error = "Each clay robot costs 2 ore and lots clay.".parse::<Recipe>().unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (33, "lots"));
+    assert!("Each clay robot is free.".parse::<Recipe>().is_err());
+    // Any run of whitespace can separate the words, including before the costs
+    let spaced = "Each  obsidian robot costs\t3 ore\tand  14 clay.".parse::<Recipe>().unwrap();
+    assert_eq!(spaced, recipe);
+    assert_eq!("Each ore robot costs\t4 ore".parse::<Recipe>().unwrap().costs, vec![("ore".to_string(), 4)]);
+    // Resources containing "and" aren't split up
+    assert_eq!("Each sand robot costs 2 sandstone".parse::<Recipe>().unwrap().costs, vec![("sandstone".to_string(), 2)]);
+    let error = "Each clay robot costs\t2 ore and\tlots clay.".parse::<Recipe>().unwrap_err();
+    assert_eq!((error.column, error.snippet.as_str()), (33, "lots"));
+}
+
+#[test]
+fn factory_matches_brute_force() {
+    let blueprints = [
+        vec!["Each ore robot costs 4 ore.", "Each clay robot costs 2 ore.",
+             "Each obsidian robot costs 3 ore and 14 clay.", "Each geode robot costs 2 ore and 7 obsidian."],
+        vec!["Each wood robot costs 1 wood.", "Each stone robot costs 2 wood.",
+             "Each gem robot costs 1 wood and 2 stone.", "Each gem robot costs 4 stone."],
+        vec!["Each a robot costs 2 a.", "Each b robot costs 1 a.", "Each c robot costs 1 a and 1 b.", "Each d robot costs 2 b and 2 c."]
+    ];
+    for (recipes, start, target, time) in [(&blueprints[0], "ore", "geode", 19), (&blueprints[1], "wood", "gem", 12), (&blueprints[2], "a", "d", 11)] {
+        let factory = Factory::new(recipes.iter().map(|r| r.parse::<Recipe>().unwrap()));
+        let plan = factory.optimise(&[(start, 1)], time, target).unwrap();
+        let resources = factory.resources();
+        let mut rates = vec![0; resources.len()];
+        rates[resources.iter().position(|r| r == start).unwrap()] = 1;
+        let target_index = resources.iter().position(|r| r == target).unwrap();
+        let expected = brute_force_production(&factory, vec![0; resources.len()], rates, time, target_index, &mut HashMap::new());
+        assert_eq!(plan.produced, expected);
+        assert_eq!(replay_plan(&factory, &[(start, 1)], time, target, &plan), expected);
+        assert!(plan.builds.windows(2).all(|pair| pair[0].minute < pair[1].minute));
+    }
+}
+
+#[test]
+fn factory_errors_and_starting_producers() {
+    let factory = Factory::new(["Each ore robot costs 4 ore.", "Each geode robot costs 2 ore."].iter().map(|r| r.parse::<Recipe>().unwrap()));
+    assert_eq!(factory.optimise(&[("ore", 1)], 10, "gold"), Err(ProductionError::UnknownResource("gold".to_string())));
+    assert_eq!(factory.optimise(&[("lead", 1)], 10, "ore").unwrap_err().to_string(), "No recipe uses or produces lead");
+    // Starting with a geode robot, and nothing to build it with
+    assert_eq!(factory.optimise(&[("geode", 2)], 5, "geode"), Ok(ProductionPlan { produced: 10, builds: vec![] }));
+    let plan = factory.optimise(&[("ore", 3)], 4, "geode").unwrap();
+    assert_eq!(plan.produced, 3);
+    assert_eq!(plan.builds.iter().map(|b| b.minute).collect::<Vec<_>>(), vec![2, 3]);
+}
@@ -1,15 +1,16 @@
 use std::{collections::BinaryHeap, io::Error};
+use aochelpers::{read_input, split_sections};
 
 fn main() -> Result<(), Error> {
-    let data = std::fs::read_to_string("./day01/input.txt")?;
+    let data = read_input("./day01/input.txt")?;
     let (part1, part2) = solution(&data);
     println!("Part 1: {:?}\nPart 2: {:?}", part1, part2);
     Ok(())
 }
 
 fn solution(data: &str) -> (i32, i32) {
-    let mut elves = data.split("\n\n")
-        .map(|e| e.split('\n')
+    let mut elves = split_sections(data).iter()
+        .map(|e| e.lines()
                 .map(|x| x.parse::<i32>()
                 .unwrap_or(0)).sum())
         .collect::<BinaryHeap<_>>();
@@ -1,56 +1,23 @@
 use std::io::Error;
-use parse_display::{Display, FromStr};
-
-#[derive(Display, FromStr, PartialEq, Debug, Copy, Clone)]
-#[display("move {quantity} from {origin} to {destination}")]
-struct CraneMove{
-    quantity: usize,
-    origin: usize,
-    destination: usize
-}
+use aochelpers::{read_input, split_sections, parse_lines, BatchMove, CraneModel, CraneMove, CrateStacks, ParseError, SingleMove, StackError};
 
 fn main() -> Result<(), Error> {
-    let in_file = std::fs::read_to_string("./day05/input.txt")?;
-    let mut data = in_file.split("\n\n");
-    let stacks = parse_stacks(data.next().unwrap());
-    let instructions = parse_instructions(data.next().unwrap());
-    println!("Part 1: {}", solution(stacks.clone(), &instructions, true));
-    println!("Part 2: {}", solution(stacks, &instructions, false));
+    let in_file = read_input("./day05/input.txt")?;
+    let data = split_sections(&in_file);
+    let stacks = data[0].parse::<CrateStacks>()?;
+    let instructions = parse_instructions(&data[1])?;
+    println!("Part 1: {}", solution(stacks.clone(), &instructions, &SingleMove).map_err(Error::other)?);
+    println!("Part 2: {}", solution(stacks, &instructions, &BatchMove).map_err(Error::other)?);
     Ok(())
 }
 
-fn parse_stacks(starting_state: &str) -> [Vec<char>; 9] {
-    let mut stacks: [Vec<char>; 9] = Default::default();
-    for line in starting_state.split('\n') {
-        let mut pos = 1;
-        while let Some(c) = line.chars().nth(pos) {
-            if c.is_ascii_uppercase() {
-                stacks[(pos -1) / 4].insert(0, c);
-            }
-            pos +=4;
-        }
-    }
-    stacks
+fn parse_instructions(instructions: &str) -> Result<Vec<CraneMove>, ParseError> {
+    parse_lines(instructions, |l| l.parse::<CraneMove>())
 }
 
-fn parse_instructions(instructions: &str) -> Vec<CraneMove> {
-    instructions.split('\n').map(|x| x.parse::<CraneMove>().unwrap()).collect()
-}
-
-fn solution(mut stacks:[Vec<char>; 9], instructions: &Vec<CraneMove>, part1: bool) -> String {
-    for instruction in instructions {
-        if part1 {
-            for _ in 0..instruction.quantity {
-                let cargo_crate = stacks[instruction.origin -1].pop().unwrap();
-                stacks[instruction.destination-1].push(cargo_crate)
-            }
-        } else {
-            let mut moved_crates = stacks[instruction.origin -1]
-                .split_off(stacks[instruction.origin -1].len() - instruction.quantity);
-            stacks[instruction.destination-1].append(&mut moved_crates);
-        }
-    }
-    stacks.iter().filter_map(|c| c.last()).collect::<String>()
+fn solution(mut stacks: CrateStacks, instructions: &[CraneMove], crane: &impl CraneModel) -> Result<String, StackError> {
+    stacks.apply_all(crane, instructions)?;
+    Ok(stacks.tops())
 }
 
 #[cfg(test)]
@@ -70,17 +37,27 @@ move 1 from 1 to 2";
 
     #[test]
     fn test_p1() {
-        let mut data = DATA.split("\n\n");
-        let stacks = parse_stacks(data.next().unwrap());
-        let instructions = parse_instructions(data.next().unwrap());
-        assert_eq!(solution(stacks,&instructions, true), "CMZ");
+        let data = split_sections(DATA);
+        let stacks = data[0].parse::<CrateStacks>().unwrap();
+        let instructions = parse_instructions(&data[1]).unwrap();
+        assert_eq!(solution(stacks, &instructions, &SingleMove), Ok("CMZ".to_string()));
     }
 
     #[test]
     fn test_p2() {
-        let mut data = DATA.split("\n\n");
-        let stacks = parse_stacks(data.next().unwrap());
-        let instructions = parse_instructions(data.next().unwrap());
-        assert_eq!(solution(stacks,&instructions, false), "MCD");
+        let data = split_sections(DATA);
+        let stacks = data[0].parse::<CrateStacks>().unwrap();
+        let instructions = parse_instructions(&data[1]).unwrap();
+        assert_eq!(solution(stacks, &instructions, &BatchMove), Ok("MCD".to_string()));
+    }
+
+    #[test]
+    fn test_empty_stack() {
+        let data = split_sections(DATA);
+        let stacks = data[0].parse::<CrateStacks>().unwrap();
+        let mut instructions = parse_instructions(&data[1]).unwrap();
+        instructions.push("move 2 from 2 to 1".parse().unwrap());
+        assert_eq!(solution(stacks, &instructions, &SingleMove),
+                   Err(StackError::NotEnoughCrates { instruction: Some(5), stack: 2, requested: 2, available: 1 }));
     }
 }
\ No newline at end of file
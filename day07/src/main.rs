@@ -1,85 +1,25 @@
 use std::error::Error;
-use parse_display::{Display, FromStr};
-use anyhow::{anyhow, Result};
-
-
-#[derive(Debug)]
-struct Directory {
-    _name: String,
-    files: Vec<FileMetadata>,
-    children: Vec<Directory>
-}
-
-impl Directory {
-    fn total_size(&self) -> i64 {
-        let files = self.files.iter().map(|f| f.size).sum::<i64>();
-        let dirs = self.children.iter().map(|d| d.total_size()).sum::<i64>();
-        files + dirs
-    }
-
-    fn part1(&self) -> i64 {
-        let size = self.total_size();
-        self.children.iter().map(|d| d.part1()).sum::<i64>() + if size < 100000 {size} else {0}
-    }
-
-    fn part2(&self, desired: i64) -> Option<i64> {
-        let size = self.total_size();
-        if size < desired {
-            None
-        } else if let Some(smaller) = self.children.iter().filter_map(|d| d.part2(desired)).min() {
-            Some(smaller)
-        } else {
-            Some(size)
-        }
-    }
-}
-
-#[derive(Display, FromStr, Debug)]
-#[display("{size} {_name}")]
-struct FileMetadata {
-    _name: String,
-    size: i64
-}
+use aochelpers::{FileSystem, ParseError};
 
+const DISK_SIZE: u64 = 70000000;
+const SPACE_NEEDED: u64 = 30000000;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let data = std::fs::read_to_string("./day07/input.txt")?;
-    if let Ok((part1, part2)) = solve(&data) {
-        println!("Part 1: {}\nPart 2: {}", part1, part2);
-    }
+    let (part1, part2) = solve(&data)?;
+    println!("Part 1: {}\nPart 2: {}", part1, part2);
     Ok(())
 }
 
-fn solve(data: &str) -> Result<(i64, i64)> {
-    let mut instruction_stack = data.lines().rev().collect::<Vec<_>>();
-    let tree = parse_tree(&mut instruction_stack)?;
-    let needed = 30000000 - (70000000 - tree.total_size());
-    Ok((tree.part1(), if let Some(n) = tree.part2(needed) {n} else {0}))
-}
-
-fn parse_tree(instructions: &mut Vec<&str>) -> Result<Directory> {
-    let instruction = instructions.pop().unwrap();
-    // Starts with "$ cd <blah>" to enter a new directory
-    // "$ cd .." indicates we've finished
-    if let Some(dirname) = instruction.strip_prefix("$ cd ") {
-        let mut next_instr = instructions.pop().unwrap_or("$ cd ..");
-        let mut files = vec![];
-        let mut children = vec![];
-        while next_instr != "$ cd .." {
-            if next_instr.starts_with("$ cd ") {
-                // going down a level, return the instruction to the stack for the 
-                // next level of recursion to consume
-                instructions.push(next_instr);
-                children.push(parse_tree(instructions)?);
-            } else if let Ok(file) = next_instr.parse::<FileMetadata>(){
-                files.push(file);
-            }
-            next_instr = instructions.pop().unwrap_or("$ cd ..");
-        }
-        Ok(Directory { _name: dirname.to_string(), files, children })
-    } else {
-        Err(anyhow!("Can't identify CWD from: {:?}", instruction))
-    }
+fn solve(data: &str) -> Result<(u64, u64), ParseError> {
+    let tree = FileSystem::from_transcript(data)?;
+    let part1 = tree.directories_with_size_at_most(100000).iter().map(|d| tree.entry(*d).size).sum();
+    let used = tree.entry(FileSystem::ROOT).size;
+    let free = DISK_SIZE.checked_sub(used)
+        .ok_or_else(|| ParseError::new(0, used.to_string(), format!("files totalling at most {} bytes", DISK_SIZE)))?;
+    let needed = SPACE_NEEDED.saturating_sub(free);
+    let part2 = tree.smallest_directory_of_at_least(needed).map(|d| tree.entry(d).size).unwrap_or(0);
+    Ok((part1, part2))
 }
 
 #[cfg(test)]
@@ -112,13 +52,11 @@ $ ls
 
     #[test]
     fn test_parts (){
-        let mut instruction_stack = DATA.lines().rev().collect::<Vec<_>>();
-        let tree = parse_tree(&mut instruction_stack).unwrap();
-        let tree_size = tree.total_size();
-        assert_eq!(tree_size, 48381165);
-        assert_eq!(tree.part1(), 95437);
-        let needed = 30000000 - (70000000 - tree_size);
-        assert_eq!(tree.part2(needed), Some(24933642));
+        let tree = FileSystem::from_transcript(DATA).unwrap();
+        assert_eq!(tree.entry(FileSystem::ROOT).size, 48381165);
+        assert_eq!(solve(DATA).unwrap(), (95437, 24933642));
+        let error = solve("$ cd /\n$ ls\n70000001 huge\n").unwrap_err();
+        assert_eq!(error.snippet, "70000001");
      }
 
 }
\ No newline at end of file
@@ -46,9 +46,8 @@ fn solution(instructions: &Vec<Step>, rope_length: usize) -> usize {
             for knot in 1..rope_length {
                 let prev = *rope.get(knot-1).unwrap();
                 let next_knot = rope.get_mut(knot).unwrap();
-                if prev != *next_knot && !prev.extended_neighbours().contains(next_knot) {
-                    next_knot.x += (prev.x - next_knot.x as i32).signum();
-                    next_knot.y += (prev.y - next_knot.y as i32).signum();
+                if prev.chebyshev_distance(next_knot) > 1 {
+                    *next_knot = next_knot.step_towards(&prev);
                 }
             }
             tail_visited.insert(*rope.get(rope_length-1).unwrap());
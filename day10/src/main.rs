@@ -1,61 +1,68 @@
 use anyhow::Error;
-// use parse_display::{Display, FromStr};
+use aochelpers::{ocr, parse_lines, scan, Control, InstructionSet, Machine, ParseError, Registers};
 
 #[derive(PartialEq, Debug)]
 enum Instruction {
     NoOp,
-    AddX(i32)
+    AddX(i64)
+}
+
+impl InstructionSet for Instruction {
+    fn cycles(&self) -> usize {
+        match self {
+            Instruction::NoOp => 1,
+            Instruction::AddX(_) => 2
+        }
+    }
+
+    fn execute(&self, registers: &mut Registers) -> Control {
+        if let Instruction::AddX(n) = self {
+            registers["x"] += n;
+        }
+        Control::Next
+    }
 }
 
 
 fn main() -> Result<(), Error> {
     let data = std::fs::read_to_string("./day10/input.txt")?;
-    let (part1, part2) = run_program(&parse_instructions(&data));
+    let (part1, part2) = run_program(parse_instructions(&data)?);
     println!("Part 1: {}", part1);
-    println!("Part 2: \n{}\n", part2);
+    match ocr(&part2, '█', ' ') {
+        Ok(letters) => println!("Part 2: {}", letters),
+        Err(_) => println!("Part 2: \n{}\n", part2)
+    }
     Ok(())
 }
 
-fn run_program(program: &[Instruction]) -> (i32, String) {
-    let mut x_register: i32 = 1;
+fn run_program(program: Vec<Instruction>) -> (i64, String) {
+    let mut machine = Machine::new(program);
+    machine.registers.set("x", 1);
     let mut part1_answer = 0;
     let mut display = String::new();
 
     let interesting_cycles = [20, 60, 100, 140, 180, 220];
-    
-    for (cycle_timer, line) in program.iter().enumerate() {
-        if cycle_timer % 40 == 0 {
+
+    machine.run(|cycle, registers| {
+        let x_register = registers["x"];
+        let column = (cycle - 1) % 40;
+        if column == 0 {
             display.push('\n')
         }
-        if interesting_cycles.contains(&(cycle_timer+1)) {
-            part1_answer += (cycle_timer as i32 +1) * x_register;
-        }
-        display.push(
-        if (x_register - ((cycle_timer) % 40) as i32 ).abs() <=1 
-              {'█'}  else  {' '});
-        match line {
-            Instruction::NoOp => {}
-            Instruction::AddX(n) => {
-                x_register += *n; 
-            }
+        if interesting_cycles.contains(&cycle) {
+            part1_answer += cycle as i64 * x_register;
         }
-    }
+        display.push(if (x_register - column as i64).abs() <= 1 {'█'} else {' '});
+    });
     (part1_answer, display)
 
 }
 
-fn parse_instructions(data:&str) -> Vec<Instruction> {
-    let mut instructions = vec![];
-    for line in data.lines() {
-        match line.strip_prefix("addx ") {
-            None =>{ instructions.push(Instruction::NoOp)}
-            Some(l) => {
-                instructions.push(Instruction::NoOp);
-                instructions.push(Instruction::AddX(l.parse::<i32>().unwrap()));
-            }
-        }
-    }
-    instructions
+fn parse_instructions(data:&str) -> Result<Vec<Instruction>, ParseError> {
+    parse_lines(data, |line| match line.trim() {
+        "noop" => Ok(Instruction::NoOp),
+        _ => scan!("addx {}", line).map(|(n,)| Instruction::AddX(n)).map_err(ParseError::from)
+    })
 }
 
 
@@ -211,8 +218,8 @@ noop";
 
     #[test]
     fn test_parts_1_and_2 (){
-        let prog = parse_instructions(DATA);
-        let (part1, part2) = run_program(&prog);
+        let prog = parse_instructions(DATA).unwrap();
+        let (part1, part2) = run_program(prog);
         let part2_expected = "
 ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  
 ███   ███   ███   ███   ███   ███   ███ 
@@ -1,99 +1,16 @@
-use std::{io::Error};
-
-#[derive(Debug, Clone,Copy, PartialEq, Eq)]
-enum Operation {
-    Multiply,
-    Add,
-    Squared,
-}
-
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Monkey{
-    items: Vec<i128>,
-    operator: Operation,
-    operatee: i128,
-    test_divisor: i128,
-    true_dest: usize,
-    false_dest: usize
-}  
-
+use std::io::Error;
+use aochelpers::{read_input, sections, Monkey, MonkeyError, MonkeySimulation, Relief};
 
 fn main() -> Result<(), Error> {
-    let data = std::fs::read_to_string("./day11/input.txt")?;
-    let monkeys = data.split("\n\n").map(parse_monkey).collect::<Vec<_>>();
-    let part1 = solution(monkeys.clone(), 20, true);
-    let part2 = solution(monkeys, 10000, false);
-
-    println!("Part 1: {:?}\nPart 2: {}", part1, part2);
+    let data = read_input("./day11/input.txt")?;
+    let monkeys = sections::<Vec<Monkey>>(&data).map_err(Error::other)?;
+    println!("Part 1: {}", solution(&monkeys, 20, Relief::Divide(3)).map_err(Error::other)?);
+    println!("Part 2: {}", solution(&monkeys, 10000, Relief::Unchanged).map_err(Error::other)?);
     Ok(())
 }
 
-fn solution(mut monkeys: Vec<Monkey>, cycles: usize, part1:bool) -> i128 {
-    let mut inspection_count = vec![0_i128; monkeys.len()];
-    let lcm_divisors:i128 = monkeys.iter().map(|m| m.test_divisor).product();
-    /*  Part 2: to stop worry_level escalating to the point it can't be stored in a number, 
-        we need to reduce it. Because each monkey performs a predictable operation, 
-        addition or multiplication, there will be many numbers for which all monkeys behave 
-        the same way.
-
-        Each of the monkeys applies a simple function, then divides by a particular prime; 
-        hence we can multiply each of these numbers together to get the point at which the 
-        rule behaviour repeats.
-
-        For each possible worry level, if f is the operation the monkey applies,
-
-        f(worry_level) % monkey_divisor == f(worry_level) % lcm_divisors % monkey_divisor
-
-        is guaranteed to hold true. 
-    
-     */
-
-    for _ in 0.. cycles{
-        for i in 0.. monkeys.len() {
-            let monkey = monkeys.get(i).unwrap().clone();
-            for item in monkey.items {
-                inspection_count[i] +=1;
-                let worry_level = (match monkey.operator {
-                    Operation::Multiply => item * monkey.operatee,
-                    Operation::Add => item + monkey.operatee,
-                    Operation::Squared => item * item,
-                } / if part1 {3} else {1}) % lcm_divisors;
-                if worry_level % monkey.test_divisor == 0 {
-                    monkeys.get_mut(monkey.true_dest).unwrap().items.push(worry_level);
-                } else {
-                    monkeys.get_mut(monkey.false_dest).unwrap().items.push(worry_level);
-                }
-
-            }
-            monkeys[i].items.truncate(0);
-        }
-    }
-    inspection_count.sort_by(|a,b| b.cmp(a));
-    inspection_count[0] * inspection_count[1]
-}
-
-
-fn parse_monkey(monkey_str: &str) -> Monkey {
-    let mut lines = monkey_str.lines();
-    lines.next();
-    let starting_items = lines.next().unwrap()[18..].split(", ").map(|x| x.parse::<i128>().unwrap()).collect::<Vec<_>>();
-    let oper_line = lines.next().unwrap();
-    let operator;
-    let operatee;
-    if &oper_line[23..] == "* old" {
-        operator = Operation::Squared;
-        operatee = 0;
-    } else {
-        operator = if oper_line.chars().nth(23).unwrap() == '+' {Operation::Add} else {Operation::Multiply};
-        operatee = oper_line[25..].parse::<i128>().unwrap();
-    }
-    let test_divisor = lines.next().unwrap()[21..].parse::<i128>().unwrap();
-    let true_dest = lines.next().unwrap()[29..].parse::<usize>().unwrap();
-    let false_dest = lines.next().unwrap()[30..].parse::<usize>().unwrap();
-
-
-    Monkey { items: starting_items, operator, operatee, test_divisor, true_dest, false_dest }
+fn solution(monkeys: &[Monkey], rounds: usize, relief: Relief) -> Result<usize, MonkeyError> {
+    MonkeySimulation::new(monkeys.to_vec(), relief).map(|simulation| simulation.run(rounds).monkey_business())
 }
 
 #[cfg(test)]
@@ -131,15 +48,20 @@ Monkey 3:
 
     #[test]
     fn test_day1() {
-        let monkeys = DATA.split("\n\n").map(parse_monkey).collect::<Vec<_>>();
-        let part1 = solution(monkeys, 20, true);
-        assert_eq!(part1,10605);
+        let monkeys = sections::<Vec<Monkey>>(DATA).unwrap();
+        assert_eq!(solution(&monkeys, 20, Relief::Divide(3)), Ok(10605));
     }
 
     #[test]
     fn test_part2() {
-        let monkeys = DATA.split("\n\n").map(parse_monkey).collect::<Vec<_>>();
-        let part2 = solution(monkeys, 10000, false);
-        assert_eq!(part2, 2713310158);
+        let monkeys = sections::<Vec<Monkey>>(DATA).unwrap();
+        assert_eq!(solution(&monkeys, 10000, Relief::Unchanged), Ok(2713310158));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_irregular_whitespace() {
+        let monkey = "Monkey 0:\r\n Starting items:  79,98\n\tOperation: new = old  *  19\n  Test: divisible by 23 \n If true: throw to monkey 2\n      If false: throw to monkey 3".parse::<Monkey>().unwrap();
+        assert_eq!((monkey.items, monkey.operation.to_string(), monkey.test_divisor, monkey.if_true, monkey.if_false),
+                   (vec![79, 98], "old * 19".to_string(), 23, 2, 3));
+    }
+}
@@ -1,75 +1,8 @@
-use std::{io::Error, cmp::Ordering, str::FromStr, num::ParseIntError};
-
-#[derive(Debug,Clone, PartialEq, Eq)]
-enum Packet {
-    Value(i32),
-    Packet(Vec<Packet>)
-}
-
-impl Ord for Packet {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self, other) {
-            (Packet::Value(s), Packet::Value(o)) => s.cmp(o),
-            (Packet::Value(n), Packet::Packet(_)) => Packet::Packet(vec![Packet::Value(*n)]).cmp(other),
-            (Packet::Packet(_), Packet::Value(n)) => self.cmp(&Packet::Packet(vec![Packet::Value(*n)])),
-            (Packet::Packet(left), Packet::Packet(right)) => {
-                for i in 0.. left.len().min(right.len()) {
-                    match left[i].cmp(&right[i]) {
-                        Ordering::Less => {
-                            return Ordering::Less
-                        },
-                        Ordering::Greater => {
-                            return Ordering::Greater
-                        }
-                        _ => {}
-                    }
-                }
-                left.len().cmp(&right.len()) 
-            }
-        }
-    }
-}
-
-impl FromStr for Packet {
-    fn from_str(line: &str) ->  Result<Self, Self::Err> {
-
-        fn process_tokens(tokens: &mut Vec<&str>) -> Result<Vec<Packet>, ParseIntError> {
-            let mut result = Vec::new();
-            while !tokens.is_empty() {
-                match tokens.pop() {
-                    Some("]") => {
-                        return Ok(result);
-                    },
-                    Some("[") => {
-                        result.push(Packet::Packet(process_tokens(tokens)?));
-                    }
-                    Some("") => {}
-                    Some(n) => {
-                        result.push(Packet::Value(n.parse::<i32>()?));
-                    }
-                    None => unreachable!()
-                };
-            }
-            Ok(result)
-        }
-
-        let binding = line.replace('[', "[,").replace(']', ",]");
-        let mut tokens = binding.split(',').rev().collect::<Vec<_>>();
-        Ok(Packet::Packet(process_tokens(&mut tokens)?))
-    }
-        
-    type Err = ParseIntError;
-
-}
-
-impl PartialOrd for Packet {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
+use std::io::Error;
+use aochelpers::{read_input, numbered_sections, parse_lines, NestedList, ParseError};
 
 fn main() -> Result<(), Error> {
-    let data = std::fs::read_to_string("./day13/input.txt")?;
+    let data = read_input("./day13/input.txt")?;
     let part1 = part1(&data)?;
     let part2 = part2(&data)?;
     println!("Part 1: {:?}", part1);
@@ -78,13 +11,24 @@ fn main() -> Result<(), Error> {
     Ok(())
 }
 
+/// Parses the blank-line separated pairs of packets, each of which must hold exactly two packets
+fn parse_pairs(data: &str) -> Result<Vec<(NestedList, NestedList)>, ParseError> {
+    numbered_sections(data).iter().map(|(first_line, section)| {
+        let packets = parse_lines(section, |line| line.parse::<NestedList>()).map_err(|e| {
+            let line = first_line + e.line - 1;
+            e.on_line(line)
+        })?;
+        match <[NestedList; 2]>::try_from(packets) {
+            Ok([left, right]) => Ok((left, right)),
+            Err(_) => Err(ParseError::new(0, section, "a pair of packets").on_line(*first_line))
+        }
+    }).collect()
+}
+
 fn part1(data: &str) -> Result<usize,Error> {
     let mut total  = 0;
-    for (i,pair) in data.split("\n\n").enumerate() {
-        let mut lines =  pair.lines();
-        let left = lines.next().unwrap().parse::<Packet>().unwrap();
-        let right = lines.next().unwrap().parse::<Packet>().unwrap();
-        if left < right {
+    for (i,(left, right)) in parse_pairs(data)?.iter().enumerate() {
+        if left.packet_cmp(right).is_lt() {
             total += i+1
         }
     }
@@ -94,13 +38,13 @@ fn part1(data: &str) -> Result<usize,Error> {
 
 fn part2(data: &str) -> Result<usize,Error> {
 
-    let mut all_packets = data.lines().filter(|l| !l.is_empty()).map(|l| l.parse::<Packet>().unwrap()).collect::<Vec<_>>();
-    let sep_2 = "[[2]]".parse::<Packet>().unwrap();
-    let sep_6 = "[[6]]".parse::<Packet>().unwrap();
+    let mut all_packets = parse_pairs(data)?.into_iter().flat_map(|(left, right)| [left, right]).collect::<Vec<_>>();
+    let sep_2 = "[[2]]".parse::<NestedList>()?;
+    let sep_6 = "[[6]]".parse::<NestedList>()?;
     all_packets.push(sep_2.clone());
     all_packets.push(sep_6.clone());
 
-    all_packets.sort();
+    all_packets.sort_by(|a, b| a.packet_cmp(b));
 
     let pos_2 = all_packets.iter().position(|p| p == &sep_2).unwrap() +1; 
     let pos_6 = all_packets.iter().position(|p| p == &sep_6).unwrap() +1; 
@@ -146,4 +90,18 @@ mod tests {
     
         assert_eq!(part2(DATA).unwrap(), 140);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_pairs("[1,2]\n[3]\n\n[[4],x]\n[5]").unwrap_err();
+        assert_eq!((error.line, error.column, error.snippet.as_str()), (4, 6, "x"));
+        // Every section must be a pair, so a stray packet can't shift the later pairs along
+        let error = parse_pairs("[1,2]\n[3]\n\n[4]\n[5]\n[6]\n\n[7]\n[8]").unwrap_err();
+        assert_eq!((error.line, error.expected.as_str()), (4, "a pair of packets"));
+        assert_eq!(parse_pairs("[1]\r\n[2]\r\n\r\n[3]").unwrap_err().line, 4);
+        assert_eq!(part1("[1]\r\n[2]\r\n\r\n\r\n[3]\r\n[1]\r\n").unwrap(), 1);
+        let error = "[1,2]]".parse::<NestedList>().unwrap_err();
+        assert_eq!((error.column, error.expected.as_str()), (6, "end of line"));
+        assert!("[1,2".parse::<NestedList>().is_err());
+    }
+}
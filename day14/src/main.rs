@@ -1,5 +1,5 @@
 use std::io::Error;
-use aochelpers::Coordinate;
+use aochelpers::{Bottom, Coordinate, SandSimulation};
 use hashbrown::HashSet;
 
 
@@ -12,43 +12,13 @@ fn main() -> Result<(), Error> {
     Ok(())
 }
 
-fn solution(mut area: HashSet<Coordinate<i32>>, part2: bool) -> i32 {
-    let mut grain_count = 0;
+fn solution(area: HashSet<Coordinate<i32>>, part2: bool) -> usize {
     let max_y = area.iter().map(|c| c.y).max().unwrap();
-    while let Some(grain_loc) = simulate_grain(&area, max_y, part2) {
-        area.insert(grain_loc);
-        grain_count += 1;
+    let mut cave = SandSimulation::new(area);
+    if part2 {
+        cave = cave.with_bottom(Bottom::Floor(max_y + 2));
     }
-    grain_count
-}
-
-fn simulate_grain(area: &HashSet<Coordinate<i32>>, max_y: i32, part2: bool) -> Option<Coordinate<i32>> {
-    let mut grain = Coordinate{x: 500, y: 0};
-    let floor = max_y + 2;
-    if area.contains(&Coordinate { x: 500, y: 0 }) {
-        return None;
-    }
-    while !area.contains(&Coordinate { x: grain.x, y: grain.y+1 }) || 
-            !area.contains(&Coordinate { x: grain.x-1, y: grain.y+1 }) ||
-            !area.contains(&Coordinate { x: grain.x+1, y: grain.y+1 }) {
-        if grain.y == floor-1 {
-            if part2 {
-                break;
-            } else {
-                return None
-            }
-        } else if !area.contains(&Coordinate { x: grain.x, y: grain.y+1 }) {
-            grain.y += 1;
-        } else if !area.contains(&Coordinate { x: grain.x-1, y: grain.y+1 }) {
-            grain.x -= 1;
-            grain.y +=1;
-        } else {
-            grain.x += 1;
-            grain.y +=1;
-        }
-    }
-    Some(grain)
-    
+    cave.fill()
 }
 
 fn build_map(data: &str) -> HashSet<Coordinate<i32>> {
@@ -64,11 +34,7 @@ fn build_map(data: &str) -> HashSet<Coordinate<i32>> {
         for next_point in points {
             start = end;
             end = next_point;
-            for x in start.x.min(end.x)..=start.x.max(end.x) {
-                for y in start.y.min(end.y)..=start.y.max(end.y)  {
-                    area.insert(Coordinate {x, y});
-                }
-            } 
+            area.extend(start.line_to(&end));
         }
     }
     area
@@ -1,93 +1,39 @@
-use std::{ io::Error, collections::{HashMap, HashSet}};
-use aochelpers::Coordinate;
-use parse_display::{Display, FromStr};
+use std::{io::Error, collections::HashSet};
+use aochelpers::{parse_lines, row_coverage, scan, uncovered_points, Coordinate, Diamond, ParseError, Rectangle};
 
-#[derive(Display, FromStr, PartialEq, Debug)]
-#[display("Sensor at x={sensor_x}, y={sensor_y}: closest beacon is at x={beacon_x}, y={beacon_y}")]
-struct SensorBeaconPair {
-    sensor_x: i128,
-    sensor_y: i128,
-    beacon_x: i128,
-    beacon_y: i128
-}
-
-#[derive(Debug,Copy,Clone)]
-struct Beacon {
-    location: Coordinate<i128>,
-    radius: i128
-}
+/// A sensor's coverage, and the beacon closest to it
+type Sensor = (Diamond, Coordinate<i64>);
 
 fn main() -> Result<(), Error> {
     let data = std::fs::read_to_string("./day15/input.txt")?;
-    let part1 = solution(&data, 2000000);
-    let part2 = solution2(&data, 4000000);
-    println!("Part 1: {:?}\nPart 2: {:?}", part1, part2.unwrap());
+    let part1 = solution(&data, 2000000)?;
+    let part2 = solution2(&data, 4000000)?.ok_or_else(|| Error::other("Every point is covered by a sensor"))?;
+    println!("Part 1: {:?}\nPart 2: {:?}", part1, part2);
     Ok(())
 }
 
-fn solution(data: &str, y: i128) -> i128 {
-    let sensors = parse_lines(data);
-    // Search space: minimum X of all sensors, minus the maximum radius of all beacons
-    // to maximum X of all sensors, plus the maximum radius of all beacons
-    let max_radius = sensors.values().map(|s| s.radius).max().unwrap();
-    let start_x = sensors.keys().map(|c| c.x).min().unwrap() - max_radius;
-    let end_x = sensors.keys().map(|c| c.x).max().unwrap() + max_radius;
-
-    let beacons = sensors.values().map(|b| b.location).collect::<HashSet<_>>();
-    let mut total = 0;
-    for x in start_x..=end_x {
-        let candidate = Coordinate{x,y};
-        if beacons.contains(&candidate) || sensors.contains_key(&candidate) {
-            continue;
-        }
-        total += sensors.iter().any(|(s,b)| candidate.manhattan_distance(s) < b.radius)as i128;
-    }
-    total
-}
-
-
-fn solution2(data: &str, radius: i128) -> Option<i128> {
-
-    let sensors = parse_lines(data);
-    for (i, (sensor, beacon)) in sensors.iter().enumerate() {
-        // Consider only sensors which have at least 1 other sensors exactly r1 + r2 +2 distance away
-        for (other, target) in sensors.iter().skip(i+1) {
-            if sensor != other && sensor.manhattan_distance(other) == beacon.radius + target.radius +2 {
-                let circle = manhattan_circle(sensor, beacon.radius+1);
-                for point in circle.iter().filter(|p| p.x >=0 && p.x <= radius && p.y >=0 && p.y <= radius) {
-                    if sensors.iter().all(|(s,b)| point.manhattan_distance(s) > b.radius) {
-                        return Some(point.x * 4000000 + point.y);
-                    }
-                }
-            }
-        }
-    }
-    Some(0)
+fn solution(data: &str, y: i64) -> Result<i64, ParseError> {
+    let sensors = parse_sensors(data)?;
+    let diamonds = sensors.iter().map(|(d, _)| *d).collect::<Vec<_>>();
+    let coverage = row_coverage(&diamonds, y);
+    let beacons_in_row = sensors.iter().map(|(_, b)| *b)
+        .filter(|b| b.y == y && coverage.iter().any(|r| r.contains(&b.x)))
+        .collect::<HashSet<_>>();
+    Ok(coverage.iter().map(|r| r.end() - r.start() + 1).sum::<i64>() - beacons_in_row.len() as i64)
 }
 
-
-fn manhattan_circle(point: &Coordinate<i128>, radius: i128) -> Vec<Coordinate<i128>> {
-
-    let mut circle = Vec::new();
-    for i in 0..radius {
-        circle.push(Coordinate{x: point.x - radius + i, y: point.y +i});
-        circle.push(Coordinate{x: point.x+i, y: point.y + radius -i});
-        circle.push(Coordinate{x: point.x +radius - i, y: point.y - i});
-        circle.push(Coordinate{x: point.x - i, y: point.y - (radius -i)});
-    }
-    circle
+fn solution2(data: &str, limit: i64) -> Result<Option<i64>, ParseError> {
+    let diamonds = parse_sensors(data)?.iter().map(|(d, _)| *d).collect::<Vec<_>>();
+    let bounds = Rectangle::new(Coordinate { x: 0, y: 0 }, Coordinate { x: limit, y: limit });
+    Ok(uncovered_points(&diamonds, &bounds).first().map(|point| point.x * 4000000 + point.y))
 }
 
-fn parse_lines(data: &str) -> HashMap<Coordinate<i128>, Beacon> {
-    let mut sensors = HashMap::new();
-    for line in data.lines() {
-        let sbp = line.parse::<SensorBeaconPair>().unwrap();
-        let sensor_loc = Coordinate{x: sbp.sensor_x, y: sbp.sensor_y};
-        let beacon_loc = Coordinate{x: sbp.beacon_x, y: sbp.beacon_y};
-        let beacon_dist = Beacon { location:beacon_loc, radius: sensor_loc.manhattan_distance(&beacon_loc) };
-        sensors.insert(sensor_loc, beacon_dist);
-    }
-    sensors
+fn parse_sensors(data: &str) -> Result<Vec<Sensor>, ParseError> {
+    parse_lines(data, |line| -> Result<Sensor, ParseError> {
+        let (sensor_x, sensor_y, beacon_x, beacon_y) = scan!("Sensor at x={}, y={}: closest beacon is at x={}, y={}", line => i64, i64, i64, i64)?;
+        let beacon = Coordinate { x: beacon_x, y: beacon_y };
+        Ok((Diamond::reaching(Coordinate { x: sensor_x, y: sensor_y }, &beacon), beacon))
+    })
 }
 
 #[cfg(test)]
@@ -111,10 +57,11 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
 
     #[test]
     fn test_part1() {
-        solution(DATA, 10);
+        assert_eq!(solution(DATA, 10), Ok(26));
     }
     #[test]
     fn test_part2() {
-        assert_eq!(solution2(DATA, 20), Some(56000011));
+        assert_eq!(solution2(DATA, 20), Ok(Some(56000011)));
+        assert_eq!(solution2("Sensor at x=2, y=18: closest beacon is at x=-2, y=q", 20).unwrap_err().line, 1);
     }
 }
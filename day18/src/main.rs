@@ -1,21 +1,18 @@
 use std::{collections::{HashSet, VecDeque}, io::Error};
-use aochelpers::{Coordinate3d, Cuboid};
+use aochelpers::{Coordinate3d, Cuboid, ParseError, parse_lines, scan};
 
 fn main() -> Result<(), Error> {
     let data = std::fs::read_to_string("./day18/input.txt")?;
-    let (part1, part2) = solution(&data);
+    let (part1, part2) = solution(&data)?;
     println!("Part 1: {:?}\nPart 2: {:?}", part1, part2);
     Ok(())
 }
 
-fn solution(data: &str) -> (usize,usize) {
+fn solution(data: &str) -> Result<(usize,usize), ParseError> {
 
-    let mut cubes = HashSet::new();
-    for line in data.lines() {
-        let mut numbers = line.split(',').map(|n| n.parse::<i32>().unwrap());
-        let cube =Coordinate3d{x: numbers.next().unwrap(), y: numbers.next().unwrap(), z: numbers.next().unwrap()};
-        cubes.insert(cube);
-    }
+    let mut cubes = parse_lines(data, |line| scan!("{},{},{}", line).map(|(x, y, z)| Coordinate3d{x, y, z}))?
+        .into_iter()
+        .collect::<HashSet<_>>();
 
     let part1 =  visible_faces(&cubes);
 
@@ -35,7 +32,7 @@ fn solution(data: &str) -> (usize,usize) {
 
     while let Some(point) = unconsidered.pop_front() {
         if bounding_box.contains(&point){
-            for neighbour in point.neighbours() {
+            for neighbour in point.neighbours_array() {
                 if !cloud.contains(&neighbour) && !unconsidered.contains(&neighbour){
                     unconsidered.push_back(neighbour);
                 }
@@ -54,13 +51,13 @@ fn solution(data: &str) -> (usize,usize) {
         }
     }
     let part2_faces =  visible_faces(&cubes);
-    (part1,part2_faces)
+    Ok((part1,part2_faces))
 
 
 }
 
 fn visible_faces(cubes: &HashSet<Coordinate3d<i32>>) -> usize {
-    cubes.len() * 6 - cubes.iter().map(|c| c.neighbours().iter().filter(|c| cubes.contains(c)).count()).sum::<usize>()
+    cubes.len() * 6 - cubes.iter().map(|c| c.neighbours_array().iter().filter(|c| cubes.contains(c)).count()).sum::<usize>()
 }
 
 #[cfg(test)]
@@ -83,8 +80,14 @@ mod tests {
 
     #[test]
     fn test_day1() {
-        let (part1, part2) = solution(DATA);
+        let (part1, part2) = solution(DATA).unwrap();
         assert_eq!(part1, 64);
         assert_eq!(part2, 58);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_error() {
+        let error = solution("1,2,3\n4,5").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+    }
+}
@@ -1,153 +1,88 @@
-use std::{io::Error, collections::{HashSet, VecDeque}, str::FromStr};
+use std::{io::Error, str::FromStr};
+use aochelpers::{scan, parse_lines, Factory, ParseError, ProductionError};
 
-#[derive(PartialEq, Debug, Copy, Clone)]
-struct Blueprint{
-    id: i32,
-    ore_robot_cost: i32,
-    clay_robot_cost: i32,
-    obsidian_robot_cost: (i32,i32),
-    geode_robot_cost: (i32,i32),
+#[derive(PartialEq, Debug, Clone)]
+struct Blueprint {
+    id: u32,
+    factory: Factory
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct ParseBluePrintError;
-
 impl FromStr for Blueprint {
-    type Err = ParseBluePrintError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let fields = s.split(' ').collect::<Vec<_>>();
-        Ok(Self{ id: fields[1].strip_suffix(':').unwrap().parse().unwrap(),
-            ore_robot_cost: fields[6].parse().unwrap(),
-            clay_robot_cost: fields[12].parse().unwrap(),
-            obsidian_robot_cost: (fields[18].parse().unwrap(), fields[21].parse().unwrap()),
-            geode_robot_cost: (fields[27].parse().unwrap(), fields[30].parse().unwrap()),
-            })
+        let (heading, recipes) = s.split_once(':').ok_or_else(|| ParseError::at(s, s, "Blueprint N: followed by robot recipes"))?;
+        let (id,) = scan!("Blueprint {}", heading)?;
+        let factory = recipes.parse::<Factory>().map_err(|mut e| {
+            if e.column > 0 {
+                e.column += heading.chars().count() + 1;
+            }
+            e
+        })?;
+        Ok(Self { id, factory })
     }
-
 }
 
-#[derive(PartialEq, Debug, Copy, Clone, Eq, Hash)]
-struct GameState {
-    ore: i32,
-    ore_robots: i32,
-    clay: i32,
-    clay_robots: i32,
-    obsidian: i32,
-    obsidian_robots: i32,
-    geodes: i32,
-    geode_robots: i32,
-    time: i32
+fn most_geodes(blueprint: &Blueprint, time_limit: u32) -> Result<u32, ProductionError> {
+    Ok(blueprint.factory.optimise(&[("ore", 1)], time_limit, "geode")?.produced)
 }
 
-impl GameState {
-    fn new() -> Self{
-        Self {
-            ore:0,
-            clay:0,
-            obsidian:0,
-            geodes: 0,
-            ore_robots:1,
-            clay_robots:0,
-            geode_robots:0,
-            obsidian_robots:0,
-            time:0
-        }
-    }
-
-    fn robots_dig(&mut self)  {
-        self.ore += self.ore_robots;
-        self.clay += self.clay_robots;
-        self.obsidian += self.obsidian_robots;
-        self.geodes += self.geode_robots;
-        self.time +=1;
-    }
+fn main() -> Result<(), Error> {
+    let data = std::fs::read_to_string("./day19/input.txt")?;
+    let blueprints = parse_lines(&data, |d| d.parse::<Blueprint>())?;
+    let part1 = blueprints.iter().map(|b| most_geodes(b, 24).map(|geodes| geodes * b.id))
+        .sum::<Result<u32, _>>().map_err(Error::other)?;
+    println!("Part 1: {}", part1);
+    let part2 = blueprints.iter().take(3).map(|b| most_geodes(b, 32))
+        .product::<Result<u32, _>>().map_err(Error::other)?;
+    println!("Part 2: {}", part2);
 
+    Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aochelpers::Recipe;
 
-fn set_robots_to_work(blueprint: &Blueprint, time_limit: i32) -> i32 {
-    let mut unseen = VecDeque::new();
-    let starting_state = GameState::new();
-    let mut best_geodes = 0;
-    let mut most_geode_robots = 0;
-    unseen.push_back(starting_state);
-
-    let mut seen_states = HashSet::new();
-
-    // Don't make more ore robots than can the max ore cost of any robot
-    let max_ore_cost = *[blueprint.ore_robot_cost, 
-                              blueprint.clay_robot_cost, 
-                              blueprint.obsidian_robot_cost.0, 
-                              blueprint.geode_robot_cost.0].iter().max().unwrap();
-
-    while let Some(mut state) = unseen.pop_front() {
-        let mut cacheable_state = state;
-        cacheable_state.time = 0;
-        if state.geode_robots < most_geode_robots || seen_states.contains(&cacheable_state) || state.time == time_limit {
-            best_geodes = best_geodes.max(state.geodes);
-            continue;
-        }
-        most_geode_robots = most_geode_robots.max(state.geode_robots);
-
-        seen_states.insert(cacheable_state);
+    const DATA: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
 
-        if state.ore >= blueprint.geode_robot_cost.0 && state.obsidian >= blueprint.geode_robot_cost.1 {
-            let mut next_state = state;
-            next_state.ore -= blueprint.geode_robot_cost.0;
-            next_state.obsidian -= blueprint.geode_robot_cost.1;
-            next_state.robots_dig();
-            next_state.geode_robots +=1;
-            unseen.push_back(next_state);
-            continue;
-        }
-
-        if state.ore >= blueprint.ore_robot_cost && state.ore_robots < max_ore_cost {
-            let mut next_state = state;
-            next_state.ore -= blueprint.ore_robot_cost;
-            next_state.robots_dig();
-            next_state.ore_robots +=1;
-            unseen.push_back(next_state);
-
-        }
-        if state.ore >= blueprint.clay_robot_cost && state.clay_robots < blueprint.obsidian_robot_cost.1 {
-            let mut next_state = state;
-            next_state.ore -= blueprint.clay_robot_cost;
-            next_state.robots_dig();
-            next_state.clay_robots +=1;
-            unseen.push_back(next_state);
-
-        }
-        if state.ore >= blueprint.obsidian_robot_cost.0 && state.clay >= blueprint.obsidian_robot_cost.1 {
-            let mut next_state = state;
-            next_state.ore -= blueprint.obsidian_robot_cost.0;
-            next_state.clay -= blueprint.obsidian_robot_cost.1;
-            next_state.robots_dig();
-            next_state.obsidian_robots +=1;
-            unseen.push_back(next_state);
-
-        }
-        state.robots_dig();
-        unseen.push_back(state);
+    #[test]
+    fn test_part1() {
+        let blueprints = parse_lines(DATA, |d| d.parse::<Blueprint>()).unwrap();
+        assert_eq!(blueprints.iter().map(|b| most_geodes(b, 24)).collect::<Vec<_>>(), vec![Ok(9), Ok(12)]);
     }
 
-    best_geodes
-}
-
-fn main() -> Result<(), Error> {
-    let data = std::fs::read_to_string("./day19/input.txt")?;
-    let mut total = 0;
-    let blueprints = data.lines().map(|d| d.parse::<Blueprint>().unwrap()).collect::<Vec<_>>();
-    for blueprint in blueprints.iter() {
-        total += set_robots_to_work(blueprint, 24) * blueprint.id;    
-    }
-    println!("Part 1: {}", total);
-    
-    let mut total = 1;
-    for blueprint in &blueprints[0..3] {
-        total *= set_robots_to_work(blueprint, 32);
+    #[test]
+    fn test_part2() {
+        let blueprints = parse_lines(DATA, |d| d.parse::<Blueprint>()).unwrap();
+        assert_eq!(blueprints.iter().map(|b| most_geodes(b, 32)).collect::<Vec<_>>(), vec![Ok(56), Ok(62)]);
+        // Without a geode robot recipe the search can't start, which is an error rather than 0 geodes
+        let no_geodes = "Blueprint 3: Each ore robot costs 2 ore. Each clay robot costs 3 ore.".parse::<Blueprint>().unwrap();
+        assert_eq!(most_geodes(&no_geodes, 32), Err(ProductionError::UnknownResource("geode".to_string())));
     }
-    println!("Part 2: {}", total);
 
-    Ok(())
+    #[test]
+    fn test_parse_blueprint() {
+        let recipe = |produces: &str, costs: &[(&str, u32)]| Recipe {
+            produces: produces.to_string(),
+            costs: costs.iter().map(|(resource, amount)| (resource.to_string(), *amount)).collect()
+        };
+        let expected = Blueprint { id: 2, factory: Factory::new([
+            recipe("ore", &[("ore", 2)]), recipe("clay", &[("ore", 3)]),
+            recipe("obsidian", &[("ore", 3), ("clay", 8)]), recipe("geode", &[("ore", 3), ("obsidian", 12)])
+        ]) };
+        let line = "Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
+        assert_eq!(line.parse::<Blueprint>(), Ok(expected.clone()));
+        let wrapped = "Blueprint 2:
+  Each ore robot costs 2 ore.
+  Each clay robot costs 3 ore.
+  Each obsidian robot costs 3 ore and 8 clay.
+  Each geode robot costs 3 ore and 12 obsidian.";
+        assert_eq!(wrapped.parse::<Blueprint>(), Ok(expected));
+        let error = parse_lines("Blueprint 2: Each ore robot costs two ore.", |d| d.parse::<Blueprint>()).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 35));
+        assert!(error.snippet.starts_with("two"));
+    }
 }
@@ -1,28 +1,27 @@
 use std::io::Error;
+use aochelpers::{parse_lines, CircularList};
 
 fn main() -> Result<(), Error> {
     let data = std::fs::read_to_string("./day20/input.txt")?;
-    let numbers = data.lines().map(|l| l.parse::<i64>().unwrap()).collect::<Vec<_>>();
-    let part1_mixed = mix(&numbers, 1, 1);
-    let zero_index = part1_mixed.iter().position(|n|n == &0).unwrap();
-    println!("Part 1: {}",  part1_mixed[(zero_index +1000) % part1_mixed.len()]+ part1_mixed[(zero_index + 2000) % part1_mixed.len()]+part1_mixed[(zero_index + 3000) % part1_mixed.len()]);
-    let part2_mixed = mix(&numbers, 10, 811589153);
-    let zero_index = part2_mixed.iter().position(|n|n == &0).unwrap();
-    println!("Part 2: {}",  part2_mixed[(zero_index +1000) % part2_mixed.len()]+ part2_mixed[(zero_index + 2000) % part2_mixed.len()]+part2_mixed[(zero_index + 3000) % part2_mixed.len()]);
+    let numbers = parse_lines(&data, |l| l.trim().parse::<i64>())?;
+    println!("Part 1: {}", grove_coordinates(&mix(&numbers, 1, 1)));
+    println!("Part 2: {}", grove_coordinates(&mix(&numbers, 10, 811589153)));
     Ok(())
 }
 
-fn mix(numbers: &[i64], repetitions: usize, decryption_key: i64) -> Vec<i64> {
-    let mut results = numbers.iter().map(|n| (n*decryption_key)).enumerate().collect::<Vec<_>>();
+fn mix(numbers: &[i64], repetitions: usize, decryption_key: i64) -> CircularList<i64> {
+    let mut results = numbers.iter().map(|n| n * decryption_key).collect::<CircularList<_>>();
     for _ in 0..repetitions {
-        for number_pos_pair in numbers.iter().map(|n| n* decryption_key ).enumerate() {
-            let current_index = results.iter().position(|x| x == &number_pos_pair).unwrap();
-            results.remove(current_index);
-            let new_index = ((current_index as i64 + number_pos_pair.1).rem_euclid(results.len() as i64)) as usize;
-            results.insert(new_index, number_pos_pair);
+        for id in 0..results.len() {
+            results.move_by(id, results[id]);
         }
     }
-    results.iter().map(|r| r.1).collect::<Vec<_>>()
+    results
+}
+
+fn grove_coordinates(mixed: &CircularList<i64>) -> i64 {
+    let zero = mixed.find(|n| *n == 0).unwrap();
+    mixed.nth_after(zero, 1000) + mixed.nth_after(zero, 2000) + mixed.nth_after(zero, 3000)
 }
 
 #[cfg(test)]
@@ -39,15 +38,17 @@ mod tests {
 
     #[test]
     fn test_day1() {
-        let numbers = DATA.lines().map(|l| l.parse::<i64>().unwrap()).collect::<Vec<_>>();
+        let numbers = parse_lines(DATA, |l| l.trim().parse::<i64>()).unwrap();
         let mixed = mix(&numbers, 1, 1);
-        assert_eq!(mixed, vec![-2, 1, 2, -3, 4, 0, 3]);
+        assert_eq!(mixed.iter().copied().collect::<Vec<_>>(), vec![-2, 1, 2, -3, 4, 0, 3]);
+        assert_eq!(grove_coordinates(&mixed), 3);
     }
 
     #[test]
     fn test_day2() {
-        let numbers = DATA.lines().map(|l| l.parse::<i64>().unwrap()).collect::<Vec<_>>();
+        let numbers = parse_lines(DATA, |l| l.trim().parse::<i64>()).unwrap();
         let mixed = mix(&numbers, 10, 811589153);
-        assert_eq!(mixed, vec![0, -2434767459, 1623178306, 3246356612, -1623178306, 2434767459, 811589153]);
+        assert_eq!(grove_coordinates(&mixed), 1623178306);
+        assert_eq!(mixed.iter().copied().collect::<Vec<_>>(), vec![0, -2434767459, 1623178306, 3246356612, -1623178306, 2434767459, 811589153]);
     }
 }
\ No newline at end of file
@@ -30,7 +30,7 @@ fn solution(mut state: HashSet<Coordinate<i32>>, rounds: usize) -> i32 {
         let mut next_spaces: HashMap<Coordinate<i32>, Vec<Coordinate<i32>>> = HashMap::new();
         let mut next_state = HashSet::new();
         'outer: for elf in state.iter() {
-            if elf.extended_neighbours().iter().all(|n| !state.contains(n)) {
+            if elf.extended_neighbours_array().iter().all(|n| !state.contains(n)) {
                 next_state.insert(*elf);
             } else {
                 for direction in directions.iter() {
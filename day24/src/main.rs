@@ -1,5 +1,5 @@
 use std::{collections::BinaryHeap, io::Error};
-use aochelpers::{Coordinate,ScoredItem};
+use aochelpers::{Coordinate, Direction, Particle, Rectangle, ScoredItem, Torus};
 use hashbrown::{HashMap, HashSet};
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -16,19 +16,7 @@ fn main() -> Result<(), Error> {
     Ok(())
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-enum Direction {
-    North,
-    South,
-    East,
-    West
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-struct Wind {
-    starting_point: Coordinate<i32>,
-    direction: Direction
-}
+type Wind = Particle<Direction>;
 
 type BlizzardCache = HashMap<(Coordinate<i32>, i32),bool>;
 
@@ -36,6 +24,7 @@ type BlizzardCache = HashMap<(Coordinate<i32>, i32),bool>;
 struct GameState {
     start: Coordinate<i32>,
     winds: Vec<Wind>,
+    valley: Torus,
     target: Coordinate<i32>,
     current_time: i32,
     reverse_path: bool,
@@ -79,7 +68,7 @@ fn get_off_the_mountain(mountain: &mut GameState) -> Option<i32>{
             candidate.x == mountain.target.x +1 ||
             candidate.y < 0 || candidate.y > max_y) {
                 let mut cache = mountain.blizzard_cache.borrow_mut();
-                let winds = cache.entry((candidate, (time +1) % cycle_time)).or_insert_with(|| is_snowy(candidate, &mountain, (time +1) % cycle_time));
+                let winds = cache.entry((candidate, (time +1) % cycle_time)).or_insert_with(|| is_snowy(candidate, mountain, (time +1) % cycle_time));
                 if ! *winds {
                     // Weight strongly in favour of distance to target; blizzards will likely significantly increase number of actual steps needed
                     let heuristic = candidate.manhattan_distance(&target) *2 + time;
@@ -104,49 +93,28 @@ fn parse_data(data: &str) -> GameState {
                     max_x = max_x.max(x);
                     max_y = max_y.max(y);
                 },
-                '^' => {winds.push(Wind{starting_point: Coordinate { x: x as i32, y: y as i32}, direction: Direction::North})},
-                'v' => {winds.push(Wind{starting_point: Coordinate { x: x as i32, y: y as i32}, direction: Direction::South})},
-                '>' => {winds.push(Wind{starting_point: Coordinate { x: x as i32, y: y as i32}, direction: Direction::East})},
-                '<' => {winds.push(Wind{starting_point: Coordinate { x: x as i32, y: y as i32}, direction: Direction::West})},
+                '^' => {winds.push(Wind{starting_point: Coordinate { x: x as i32, y: y as i32}, heading: Direction::North})},
+                'v' => {winds.push(Wind{starting_point: Coordinate { x: x as i32, y: y as i32}, heading: Direction::South})},
+                '>' => {winds.push(Wind{starting_point: Coordinate { x: x as i32, y: y as i32}, heading: Direction::East})},
+                '<' => {winds.push(Wind{starting_point: Coordinate { x: x as i32, y: y as i32}, heading: Direction::West})},
                 _ => unimplemented!("Can't parse {}", c)
             };
         }
     }
+    let target = Coordinate {x: max_x as i32, y: max_y as i32};
     GameState { start: Coordinate {x: 1, y: 0}, 
                 winds, 
-                target: Coordinate {x: max_x as i32, y: max_y as i32}, 
+                valley: Torus(Rectangle::new(Coordinate { x: 1, y: 1 }, Coordinate { x: target.x, y: target.y - 1 })),
+                target, 
                 current_time: 0, 
                 reverse_path: false, 
                 blizzard_cache:Rc::new(RefCell::new(HashMap::new()))}
 }
 
 fn is_snowy(location: Coordinate<i32>, state: &GameState, turn: i32) ->bool{
-    let bottom_left = state.start.max(state.target);
-    for wind in state.winds.iter() {
-        match wind.direction {
-            Direction::North =>  {
-                if wind.starting_point.x == location.x && (wind.starting_point.y -1 - turn).rem_euclid(bottom_left.y -1 ) +1 == location.y { 
-                   return true;
-                }
-            },
-            Direction::South => {
-                if wind.starting_point.x == location.x && (wind.starting_point.y -1 + turn) % (bottom_left.y -1) +1 == location.y {
-                    return true;
-                }
-            }
-            Direction::East => {    
-                if wind.starting_point.y == location.y && (wind.starting_point.x -1 + turn) % (bottom_left.x ) +1 == location.x {
-                    return true;
-                }
-            },
-            Direction::West => {
-                if wind.starting_point.y == location.y && (wind.starting_point.x -1 - turn).rem_euclid(bottom_left.x ) +1 == location.x { 
-                    return true;
-                }
-            },
-        }
-    }
-    false
+    state.winds.iter()
+        .filter(|wind| wind.starting_point.x == location.x || wind.starting_point.y == location.y)
+        .any(|wind| wind.position_after(turn as usize, &state.valley).map(|(position, _)| position) == Some(location))
 }
 
 fn lcm(first: i32, second: i32) -> i32 {